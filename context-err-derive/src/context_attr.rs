@@ -0,0 +1,96 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    spanned::Spanned,
+    visit_mut::{self, VisitMut},
+    Expr, GenericArgument, Item, ItemFn, PathArguments, ReturnType, Type,
+};
+
+/// Rewrite every `?` in the body of `item` so that errors of a contextual source type of the
+/// function's error type get the context described by `message`, a format string and arguments.
+pub(crate) fn expand(message: TokenStream2, mut item: ItemFn) -> darling::Result<TokenStream2> {
+    if message.is_empty() {
+        return Err(darling::Error::custom(
+            "expected a format string describing the context, as in `#[context(\"loading {path}\")]`",
+        ));
+    }
+    let error = error_type(&item.sig.output)?;
+
+    WrapTries {
+        error: &error,
+        message: &message,
+    }
+    .visit_block_mut(&mut item.block);
+    Ok(quote!(#item))
+}
+
+/// The error type `E` of a function returning `Result<T, E>`.
+fn error_type(output: &ReturnType) -> darling::Result<Type> {
+    let err = || {
+        darling::Error::custom(
+            "`#[context]` adds context for a function's error type, \
+             so the function must return `Result<T, E>`, spelled out",
+        )
+    };
+    let ReturnType::Type(_, ty) = output else {
+        return Err(err().with_span(&output));
+    };
+    let Type::Path(path) = &**ty else {
+        return Err(err().with_span(ty));
+    };
+    let segment = path
+        .path
+        .segments
+        .last()
+        .ok_or_else(|| err().with_span(ty))?;
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return Err(err().with_span(ty));
+    };
+    let types = args
+        .args
+        .iter()
+        .filter_map(|arg| match arg {
+            GenericArgument::Type(ty) => Some(ty),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    match (segment.ident == "Result", types.as_slice()) {
+        (true, [_, error]) => Ok((*error).clone()),
+        _ => Err(err().with_span(ty)),
+    }
+}
+
+/// Wraps the operand of each `?` which belongs to the function itself.
+struct WrapTries<'a> {
+    error: &'a Type,
+    message: &'a TokenStream2,
+}
+
+impl VisitMut for WrapTries<'_> {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        match expr {
+            // A `?` in a closure or async block returns from that, not from the function.
+            Expr::Closure(_) | Expr::Async(_) => {}
+            Expr::Try(try_expr) => {
+                self.visit_expr_mut(&mut try_expr.expr);
+                let inner = &try_expr.expr;
+                let error = self.error;
+                let message = self.message;
+                let wrapped = quote::quote_spanned! {inner.span()=>
+                    {
+                        #[allow(unused_imports)]
+                        use ::context_err::__private::{AddContext as _, PassThrough as _};
+                        (&::context_err::__private::ContextWrap::<_, #error>::new(#inner))
+                            .__context(|| ::std::format!(#message))
+                    }
+                };
+                *try_expr.expr = syn::parse2(wrapped).expect("wrapped operand is an expression");
+            }
+            _ => visit_mut::visit_expr_mut(self, expr),
+        }
+    }
+
+    fn visit_item_mut(&mut self, _: &mut Item) {
+        // Nested items have `?`s of their own.
+    }
+}
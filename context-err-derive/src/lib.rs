@@ -1,12 +1,167 @@
-use darling::FromMeta;
+mod context_attr;
+
+use darling::{util::Flag, FromMeta};
 use proc_macro::TokenStream;
-use quote::{quote, quote_spanned};
-use syn::{parse_macro_input, spanned::Spanned, AttributeArgs, Item, ItemEnum, ItemStruct};
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote, ToTokens};
+use syn::{
+    parse_macro_input, parse_quote, Attribute, AttributeArgs, Fields, GenericParam, Generics,
+    Ident, Item, ItemEnum, ItemFn, ItemStruct, LitStr, Meta, NestedMeta, Type, Visibility,
+};
 
 #[derive(Debug, FromMeta)]
 struct Args {
     #[darling(rename = "trait")]
     trait_: Option<String>,
+    /// The visibility of the dedicated context trait, if not that of the error type.
+    trait_vis: Option<Visibility>,
+    /// The name of the dedicated trait's method which adds context, if not `context`.
+    method: Option<Ident>,
+    /// The name of the dedicated trait's method which adds lazily-computed context.
+    ///
+    /// This defaults to `with_` followed by the name of `method`.
+    with_method: Option<Ident>,
+    /// Record the location at which context was added.
+    location: Flag,
+    /// Capture a backtrace when context is added, for every contextual struct or variant.
+    backtrace: Flag,
+    /// Store a `context_err::StructuredContext` instead of a plain string.
+    structured: Flag,
+    /// Store context in this type instead of a `String`.
+    context_type: Option<Type>,
+    /// How contextual structs and variants without an explicit `fmt` are displayed.
+    display: Option<DisplayStyle>,
+    /// The error type implements `Debug` by hand, rather than deriving it.
+    manual_debug: Flag,
+    /// Generate a fieldless enum mirroring the variants of the error enum, and a `kind` method.
+    kind: Flag,
+}
+
+/// How a contextual struct or variant is displayed, if it has no explicit `fmt`.
+#[derive(Debug, Default, Clone, Copy, FromMeta)]
+enum DisplayStyle {
+    /// Display only the context.
+    #[default]
+    #[darling(rename = "context")]
+    Context,
+    /// Display the context followed by the source error, as `"{context}: {source}"`.
+    #[darling(rename = "chain")]
+    Chain,
+}
+
+impl DisplayStyle {
+    fn template(self, has_source: bool) -> &'static str {
+        match (self, has_source) {
+            (DisplayStyle::Chain, true) => "{context}: {source}",
+            _ => "{context}",
+        }
+    }
+}
+
+impl Args {
+    /// Check for arguments which are individually valid, but not together.
+    fn validate(&self) -> darling::Result<()> {
+        if let (true, Some(context_type)) = (self.structured.is_present(), &self.context_type) {
+            return Err(darling::Error::custom(
+                "`structured` errors always store a `StructuredContext`; remove `context_type`",
+            )
+            .with_span(context_type));
+        }
+        if let (None, Some(trait_vis)) = (&self.trait_, &self.trait_vis) {
+            return Err(darling::Error::custom(
+                "`trait_vis` sets the visibility of a dedicated trait; add `trait = \"...\"`",
+            )
+            .with_span(trait_vis));
+        }
+        if self.trait_.is_none() {
+            let mut errors = darling::Error::accumulator();
+            for method in [&self.method, &self.with_method].into_iter().flatten() {
+                errors.push(
+                    darling::Error::custom(
+                        "the methods of `context_err::Context` can't be renamed; \
+                         add `trait = \"...\"` to generate a dedicated trait",
+                    )
+                    .with_span(method),
+                );
+            }
+            errors.finish()?;
+        }
+        Ok(())
+    }
+
+    /// The name of the dedicated context trait to generate, if one was requested.
+    fn trait_ident(&self) -> Option<Ident> {
+        self.trait_.as_deref().map(|name| format_ident!("{}", name))
+    }
+
+    /// The names of the methods which add context and lazily-computed context.
+    fn method_idents(&self) -> (Ident, Ident) {
+        let method = self
+            .method
+            .clone()
+            .unwrap_or_else(|| format_ident!("context"));
+        let with_method = self
+            .with_method
+            .clone()
+            .unwrap_or_else(|| format_ident!("with_{}", method));
+        (method, with_method)
+    }
+
+    /// The type of the context field.
+    fn context_type(&self) -> TokenStream2 {
+        if let Some(context_type) = &self.context_type {
+            context_type.to_token_stream()
+        } else if self.structured.is_present() {
+            quote!(::context_err::StructuredContext)
+        } else {
+            quote!(::std::string::String)
+        }
+    }
+
+    /// The type through which accessors expose the context, and an expression producing it from
+    /// a reference to the context field.
+    ///
+    /// String-like contexts are exposed as `&str`, so that accessors don't depend on the storage
+    /// type.
+    fn context_ref(&self, context: TokenStream2) -> (TokenStream2, TokenStream2) {
+        if self.structured.is_present() {
+            (quote!(&::context_err::StructuredContext), context)
+        } else {
+            (quote!(&str), self.context_str(context))
+        }
+    }
+
+    /// An expression producing a `&str` from a reference to the context field.
+    fn context_str(&self, context: TokenStream2) -> TokenStream2 {
+        if self.structured.is_present() {
+            quote!(#context.message())
+        } else {
+            quote!(<_ as ::core::convert::AsRef<str>>::as_ref(#context))
+        }
+    }
+
+    /// Convert `message`, which implements `IntoContext`, into the context type.
+    fn make_context(&self, message: TokenStream2) -> TokenStream2 {
+        let context_type = self.context_type();
+        quote!(<_ as ::context_err::IntoContext<#context_type>>::into_context(#message))
+    }
+}
+
+/// Arguments to an `#[error(contextual, ...)]` attribute on a struct or variant.
+#[derive(Debug, FromMeta)]
+struct ContextualArgs {
+    /// Always present; this is how we tell our attributes apart from `thiserror`'s.
+    #[allow(dead_code)]
+    contextual: Flag,
+    /// This struct or variant is produced when adding context to `None`.
+    none: Flag,
+    /// Capture a backtrace when context is added to this struct or variant.
+    backtrace: Flag,
+    /// Display this struct or variant with this format string.
+    ///
+    /// It may refer to the fields generated by `derive_context_err` by name, as `{context}`,
+    /// `{source}`, and `{location}`, whether or not the fields are named.
+    fmt: Option<LitStr>,
 }
 
 #[proc_macro_attribute]
@@ -19,20 +174,1249 @@ pub fn derive_context_err(args: TokenStream, item: TokenStream) -> TokenStream {
         Err(err) => return TokenStream::from(err.write_errors()),
     };
 
-    match item {
+    let expanded = match item {
         Item::Enum(item) => derive_for_enum(args, item),
         Item::Struct(item) => derive_for_struct(args, item),
-        _ => quote_spanned! {
-            item.span() => compile_error!("this macro only works for structs and enums")
+        _ => Err(
+            darling::Error::custom("this macro only works for structs and enums").with_span(&item),
+        ),
+    };
+    match expanded {
+        Ok(expanded) => expanded.into(),
+        Err(err) => err.write_errors().into(),
+    }
+}
+
+/// Add context to every error of a contextual source type which a function returns with `?`.
+///
+/// The arguments are those of `format!`, and are evaluated only if there is an error.
+#[proc_macro_attribute]
+pub fn context(args: TokenStream, item: TokenStream) -> TokenStream {
+    let item = parse_macro_input!(item as ItemFn);
+    match context_attr::expand(args.into(), item) {
+        Ok(expanded) => expanded.into(),
+        Err(err) => err.write_errors().into(),
+    }
+}
+
+fn derive_for_enum(args: Args, mut item: ItemEnum) -> darling::Result<TokenStream2> {
+    let mut errors = darling::Error::accumulator();
+    errors.handle(args.validate());
+
+    let ident = &item.ident;
+    let mut contextuals = Vec::new();
+    for variant in item.variants.iter_mut() {
+        let variant_ident = &variant.ident;
+        if let Some(Some(contextual)) = errors.handle(rewrite_contextual(
+            &args,
+            &mut variant.attrs,
+            &mut variant.fields,
+            variant_ident,
+            quote!(#ident::#variant_ident),
+        )) {
+            contextuals.push(contextual);
+        }
+    }
+
+    errors.handle(check_unique_sources(&contextuals));
+    errors.handle(check_generic_sources(&item.generics, &contextuals));
+    errors.handle(check_accessor_names(&contextuals));
+    errors.handle(check_constructors(&args, &contextuals));
+    if !contextuals.is_empty() {
+        errors.handle(check_debug(&args, &item.attrs, ident));
+    }
+    errors.finish()?;
+
+    item.attrs
+        .insert(0, parse_quote!(#[derive(thiserror::Error)]));
+    let error = ErrorType::new(&item.vis, &item.ident, &item.generics);
+    let variant_accessors = variant_accessors(&args, &error, &contextuals);
+    let constructors = message_constructors(&args, &error, &contextuals, false);
+    let kind = args.kind.is_present().then(|| kind_enum(&error, &item));
+    let mut expanded = expand(&args, &error, contextuals, &item);
+    expanded.extend(constructors);
+    expanded.extend(variant_accessors);
+    expanded.extend(kind);
+    Ok(expanded)
+}
+
+fn derive_for_struct(args: Args, mut item: ItemStruct) -> darling::Result<TokenStream2> {
+    let mut errors = darling::Error::accumulator();
+    errors.handle(args.validate());
+
+    let ident = &item.ident;
+    let mut contextuals = Vec::new();
+    if let Some(Some(contextual)) = errors.handle(rewrite_contextual(
+        &args,
+        &mut item.attrs,
+        &mut item.fields,
+        ident,
+        quote!(#ident),
+    )) {
+        contextuals.push(contextual);
+    }
+
+    if !contextuals.is_empty() {
+        errors.handle(check_debug(&args, &item.attrs, ident));
+    }
+    if args.kind.is_present() {
+        errors.push(
+            darling::Error::custom(format!(
+                "`kind` mirrors the variants of an error enum, but `{ident}` is a struct"
+            ))
+            .with_span(ident),
+        );
+    }
+    errors.finish()?;
+
+    item.attrs
+        .insert(0, parse_quote!(#[derive(thiserror::Error)]));
+    let error = ErrorType::new(&item.vis, &item.ident, &item.generics);
+    let constructors = message_constructors(&args, &error, &contextuals, true);
+    let mut expanded = expand(&args, &error, contextuals, &item);
+    expanded.extend(constructors);
+    Ok(expanded)
+}
+
+/// The error type being derived, along with its visibility and generics.
+///
+/// Generated impls introduce generic parameters of their own, which are prefixed with `__` so as
+/// not to shadow the error type's parameters, or any types used by its contextual variants.
+struct ErrorType<'a> {
+    vis: &'a Visibility,
+    ident: &'a Ident,
+    generics: &'a Generics,
+}
+
+impl<'a> ErrorType<'a> {
+    fn new(vis: &'a Visibility, ident: &'a Ident, generics: &'a Generics) -> Self {
+        ErrorType {
+            vis,
+            ident,
+            generics,
+        }
+    }
+
+    /// The error type with its generic arguments, such as `Error<'a, E>`.
+    fn ty(&self) -> TokenStream2 {
+        let ident = self.ident;
+        let (_, ty_generics, _) = self.generics.split_for_impl();
+        quote!(#ident #ty_generics)
+    }
+
+    /// The generics of a context trait implementation: those of the error type, plus the type
+    /// `__T` of the wrapped success value.
+    fn impl_generics(&self) -> Generics {
+        let mut generics = self.generics.clone();
+        let lifetimes = generics.lifetimes().count();
+        generics
+            .params
+            .insert(lifetimes, GenericParam::Type(parse_quote!(__T)));
+        generics
+    }
+}
+
+/// Emit the rewritten item along with the context trait and its implementations.
+fn expand(
+    args: &Args,
+    error: &ErrorType,
+    contextuals: Vec<Contextual>,
+    item: &impl ToTokens,
+) -> TokenStream2 {
+    // A dedicated trait is only generated on request; by default we implement the shared one.
+    let context_trait = args
+        .trait_ident()
+        .map(|trait_ident| generate_context_trait(args, &trait_ident, error));
+    let impls = contextuals
+        .iter()
+        .filter(|contextual| contextual.inner.is_some() || contextual.none)
+        .map(|contextual| context_impl(args, error, contextual));
+
+    let location_accessor = args
+        .location
+        .is_present()
+        .then(|| location_accessor(error, &contextuals));
+    let backtrace_accessor = contextuals
+        .iter()
+        .any(|contextual| contextual.fields.contains(&FieldKind::Backtrace))
+        .then(|| backtrace_accessor(error, &contextuals));
+    let fields_accessor = args
+        .structured
+        .is_present()
+        .then(|| fields_accessor(error, &contextuals));
+    let contextual_error = contextual_error_impl(args, error, &contextuals);
+
+    quote! {
+        #item
+        #context_trait
+        #( #impls )*
+        #location_accessor
+        #backtrace_accessor
+        #fields_accessor
+        #contextual_error
+    }
+}
+
+/// Ensure that no two contextual variants wrap the same source type, or are both marked `none`.
+///
+/// Either would produce conflicting implementations of the context trait, and rustc's
+/// diagnostic for that doesn't point at the cause.
+fn check_unique_sources(contextuals: &[Contextual]) -> darling::Result<()> {
+    let mut errors = darling::Error::accumulator();
+    for (idx, second) in contextuals.iter().enumerate() {
+        // Only contextuals which implement the context trait can conflict.
+        let key = |contextual: &Contextual| match &contextual.inner {
+            Some(inner) => Some(Some(inner.to_token_stream().to_string())),
+            None if contextual.none => Some(None),
+            None => None,
+        };
+        if key(second).is_none() {
+            continue;
+        }
+        let Some(first) = contextuals[..idx]
+            .iter()
+            .find(|first| key(first) == key(second))
+        else {
+            continue;
+        };
+
+        let (message, note) = match &second.inner {
+            Some(inner) => {
+                let inner = inner.to_token_stream().to_string().replace(' ', "");
+                (
+                    format!(
+                        "`{}` wraps `{inner}`, which is already wrapped by contextual variant `{}`\n\
+                         help: each source type may only be wrapped by one contextual variant, \
+                         because `.context` must know which variant to produce; \
+                         remove `contextual` from this variant and construct it with `map_err`, \
+                         or wrap a distinct type",
+                        second.ident, first.ident,
+                    ),
+                    format!("`{}` first wraps `{inner}` here", first.ident),
+                )
+            }
+            None => (
+                format!(
+                    "`{}` is marked `none`, but so is `{}`\n\
+                     help: only one variant may be marked `none`, \
+                     because `.context` on an `Option` must know which variant to produce",
+                    second.ident, first.ident,
+                ),
+                format!("`{}` is first marked `none` here", first.ident),
+            ),
+        };
+        errors.push(darling::Error::custom(message).with_span(&second.ident));
+        errors.push(darling::Error::custom(note).with_span(&first.ident));
+    }
+    errors.finish()
+}
+
+/// Ensure that a contextual variant wrapping a bare type parameter is the only one wrapping a
+/// source, since the parameter might be instantiated with any of the other source types.
+fn check_generic_sources(generics: &Generics, contextuals: &[Contextual]) -> darling::Result<()> {
+    let is_param = |inner: &Type| match inner {
+        Type::Path(path) if path.qself.is_none() => generics
+            .type_params()
+            .any(|param| path.path.is_ident(&param.ident)),
+        _ => false,
+    };
+    let Some(generic) = contextuals
+        .iter()
+        .find(|contextual| contextual.inner.as_ref().is_some_and(is_param))
+    else {
+        return Ok(());
+    };
+
+    let mut errors = darling::Error::accumulator();
+    for other in contextuals {
+        if other.ident == generic.ident || other.inner.is_none() {
+            continue;
+        }
+        let param = generic.inner.to_token_stream();
+        errors.push(
+            darling::Error::custom(format!(
+                "`{}` may wrap the same type as `{}`, which wraps any `{param}`\n\
+                 help: a contextual variant wrapping a type parameter must be the only \
+                 contextual variant with a source; \
+                 remove `contextual` from this variant and construct it with `map_err`",
+                other.ident, generic.ident,
+            ))
+            .with_span(&other.ident),
+        );
+    }
+    errors.finish()
+}
+
+/// Ensure that no two contextual variants have the same name in snake case, such as `HttpError`
+/// and `HTTPError`.
+///
+/// Their accessors, and constructors if they carry only their context, would have the same names,
+/// and rustc's diagnostic for that points only at the attribute.
+fn check_accessor_names(contextuals: &[Contextual]) -> darling::Result<()> {
+    let mut errors = darling::Error::accumulator();
+    for (idx, second) in contextuals.iter().enumerate() {
+        let name = snake_case(&second.ident.to_string());
+        let Some(first) = contextuals[..idx]
+            .iter()
+            .find(|first| snake_case(&first.ident.to_string()) == name)
+        else {
+            continue;
+        };
+
+        let methods = if first.inner.is_none() && second.inner.is_none() {
+            format!(
+                "accessors and constructor of `{}` would be named `{name}`, ",
+                second.ident
+            )
+        } else {
+            format!("accessors of `{}` would be named ", second.ident)
+        };
+        errors.push(
+            darling::Error::custom(format!(
+                "the {methods}`is_{name}`, `as_{name}`, and `into_{name}`, like those of `{}`\n\
+                 help: rename one of the variants",
+                first.ident,
+            ))
+            .with_span(&second.ident),
+        );
+        errors.push(
+            darling::Error::custom(format!("`{}` first uses these names here", first.ident))
+                .with_span(&first.ident),
+        );
+    }
+    errors.finish()
+}
+
+/// Ensure that the constructor of each variant which carries only its context has a valid name,
+/// which doesn't collide with another generated method.
+fn check_constructors(args: &Args, contextuals: &[Contextual]) -> darling::Result<()> {
+    let mut generated = Vec::new();
+    if args.location.is_present() {
+        generated.push("location".to_owned());
+    }
+    if contextuals
+        .iter()
+        .any(|contextual| contextual.fields.contains(&FieldKind::Backtrace))
+    {
+        generated.push("backtrace".to_owned());
+    }
+    if args.structured.is_present() {
+        generated.push("context_fields".to_owned());
+    }
+    if args.kind.is_present() {
+        generated.push("kind".to_owned());
+    }
+    for contextual in contextuals {
+        let name = snake_case(&contextual.ident.to_string());
+        generated.extend(["is", "as", "into"].map(|prefix| format!("{prefix}_{name}")));
+    }
+
+    // Constructors which collide with each other are caught by `check_accessor_names`.
+    let mut errors = darling::Error::accumulator();
+    for contextual in contextuals
+        .iter()
+        .filter(|contextual| contextual.inner.is_none())
+    {
+        let ident = &contextual.ident;
+        let name = snake_case(&ident.to_string());
+        let problem = if matches!(name.as_str(), "crate" | "self" | "super") {
+            format!("`{name}` is not a valid method name")
+        } else if generated.contains(&name) {
+            format!("this collides with the generated `{name}` method")
+        } else {
+            continue;
+        };
+        errors.push(
+            darling::Error::custom(format!(
+                "the constructor of `{ident}` would be named `{name}`, but {problem}\n\
+                 help: rename the variant"
+            ))
+            .with_span(ident),
+        );
+    }
+    errors.finish()
+}
+
+/// Ensure that an error type with contextual variants derives `Debug`, which `thiserror` needs.
+///
+/// Derives placed above `#[derive_context_err]` don't appear in its input, and are applied to the
+/// item as written rather than as rewritten, producing baffling errors about field counts.
+fn check_debug(args: &Args, attrs: &[Attribute], ident: &Ident) -> darling::Result<()> {
+    if args.manual_debug.is_present() {
+        return Ok(());
+    }
+
+    let derives_debug = attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("derive"))
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(Meta::List(list)) => Some(list.nested),
+            _ => None,
+        })
+        .flatten()
+        .any(|nested| match nested {
+            NestedMeta::Meta(Meta::Path(path)) => path
+                .segments
+                .last()
+                .is_some_and(|segment| segment.ident == "Debug"),
+            _ => false,
+        });
+    if derives_debug {
+        return Ok(());
+    }
+
+    Err(darling::Error::custom(format!(
+        "`{ident}` must derive `Debug` below `#[derive_context_err]`\n\
+         help: derives above `#[derive_context_err]` apply to `{ident}` as written, \
+         before its contextual variants are rewritten; \
+         move `#[derive(Debug)]` below `#[derive_context_err]`, \
+         or use `#[derive_context_err(manual_debug)]` if you implement `Debug` by hand"
+    ))
+    .with_span(ident))
+}
+
+/// Find and parse the `#[error(contextual, ...)]` attribute, if any.
+///
+/// `#[error(...)]` attributes which don't include `contextual` belong to `thiserror`, and are
+/// ignored.
+fn contextual_args(attrs: &[Attribute]) -> darling::Result<Option<(usize, ContextualArgs)>> {
+    for (idx, attr) in attrs.iter().enumerate() {
+        if !attr.path.is_ident("error") {
+            continue;
+        }
+        let Ok(Meta::List(list)) = attr.parse_meta() else {
+            continue;
+        };
+        let is_contextual = list.nested.iter().any(
+            |nested| matches!(nested, NestedMeta::Meta(Meta::Path(path)) if path.is_ident("contextual")),
+        );
+        if is_contextual {
+            let nested = list.nested.into_iter().collect::<Vec<_>>();
+            let args = ContextualArgs::from_list(&nested)?;
+            return Ok(Some((idx, args)));
+        }
+    }
+    Ok(None)
+}
+
+/// A field of a rewritten contextual struct or variant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FieldKind {
+    Source,
+    Context,
+    Location,
+    Backtrace,
+}
+
+impl FieldKind {
+    /// The name of the local binding which holds this field's value in generated code.
+    ///
+    /// This is also the field name when fields are named, except for the source field, which
+    /// keeps the name the user gave it.
+    fn binding(self) -> Ident {
+        match self {
+            FieldKind::Source => format_ident!("inner"),
+            FieldKind::Context => format_ident!("context"),
+            FieldKind::Location => format_ident!("location"),
+            FieldKind::Backtrace => format_ident!("backtrace"),
+        }
+    }
+
+    /// The kind of field referred to by a placeholder in a `fmt` string, if any.
+    fn from_placeholder(name: &str) -> Option<Self> {
+        match name {
+            "source" => Some(FieldKind::Source),
+            "context" => Some(FieldKind::Context),
+            "location" => Some(FieldKind::Location),
+            _ => None,
+        }
+    }
+}
+
+/// A contextual struct or variant, after rewriting.
+struct Contextual {
+    /// The path to the struct or variant, used to construct and match it.
+    path: TokenStream2,
+    /// The type of the wrapped source error, or `None` if this carries only its context.
+    inner: Option<Type>,
+    /// This is produced when adding context to `None`.
+    none: bool,
+    /// The name of the source field if fields are named, or `None` if they are unnamed.
+    ///
+    /// Structs and variants without a source are always unnamed.
+    source_name: Option<Ident>,
+    /// The fields of the rewritten struct or variant, in order.
+    fields: Vec<FieldKind>,
+    /// The name of the struct or variant.
+    ident: Ident,
+}
+
+impl Contextual {
+    fn field_name(&self, kind: FieldKind) -> Ident {
+        match (kind, &self.source_name) {
+            (FieldKind::Source, Some(name)) => name.clone(),
+            _ => kind.binding(),
         }
-        .into(),
     }
+
+    /// An expression constructing the error from bindings named for each field.
+    fn construct(&self) -> TokenStream2 {
+        let path = &self.path;
+        let bindings = self.fields.iter().map(|kind| kind.binding());
+        if self.source_name.is_some() {
+            let names = self.fields.iter().map(|&kind| self.field_name(kind));
+            quote!(#path { #( #names: #bindings ),* })
+        } else {
+            quote!(#path( #( #bindings ),* ))
+        }
+    }
+
+    /// A pattern matching this struct or variant, binding only the fields of the given kinds.
+    fn pattern(&self, kinds: &[FieldKind]) -> TokenStream2 {
+        let path = &self.path;
+        if self.source_name.is_some() {
+            let names = kinds.iter().map(|&kind| self.field_name(kind));
+            let bindings = kinds.iter().map(|kind| kind.binding());
+            quote!(#path { #( #names: #bindings, )* .. })
+        } else {
+            let patterns = self.fields.iter().map(|field| {
+                if kinds.contains(field) {
+                    field.binding().to_token_stream()
+                } else {
+                    quote!(_)
+                }
+            });
+            quote!(#path( #( #patterns ),* ))
+        }
+    }
+
+    /// The format string for the `#[error(...)]` attribute which replaces `#[error(contextual)]`.
+    ///
+    /// `template` refers to generated fields by name; those references are replaced by whatever
+    /// refers to that field in this layout. Other placeholders are left alone.
+    fn format(&self, template: &LitStr) -> LitStr {
+        let format = replace_placeholders(&template.value(), |name| {
+            let kind = FieldKind::from_placeholder(name)?;
+            if self.source_name.is_some() {
+                return self
+                    .fields
+                    .contains(&kind)
+                    .then(|| self.field_name(kind).to_string());
+            }
+            let idx = self.fields.iter().position(|&field| field == kind)?;
+            Some(idx.to_string())
+        });
+        LitStr::new(&format, template.span())
+    }
+
+    /// Rewrite `fields` to match this struct or variant's layout.
+    fn rewrite_fields(&self, args: &Args, fields: &mut Fields) {
+        let attrs = self.fields.iter().map(|kind| match kind {
+            FieldKind::Source => quote!(#[source]),
+            _ => quote!(),
+        });
+        let types = self.fields.iter().map(|kind| match kind {
+            FieldKind::Source => {
+                let inner = self.inner.as_ref().expect("source fields have a type");
+                inner.to_token_stream()
+            }
+            FieldKind::Context => args.context_type(),
+            FieldKind::Location => quote!(&'static ::core::panic::Location<'static>),
+            FieldKind::Backtrace => quote!(::std::backtrace::Backtrace),
+        });
+        if self.source_name.is_some() {
+            let names = self.fields.iter().map(|&kind| self.field_name(kind));
+            *fields = Fields::Named(parse_quote!({ #( #attrs #names: #types ),* }));
+        } else {
+            *fields = Fields::Unnamed(parse_quote!(( #( #attrs #types ),* )));
+        }
+    }
+}
+
+/// Rewrite a struct or variant marked `#[error(contextual)]`.
+///
+/// `ident` is the name of the struct or variant, and `path` is the path to it, used to construct
+/// it.
+/// Returns `None` if the struct or variant is not contextual, in which case it is left untouched.
+fn rewrite_contextual(
+    args: &Args,
+    attrs: &mut [Attribute],
+    fields: &mut Fields,
+    ident: &Ident,
+    path: TokenStream2,
+) -> darling::Result<Option<Contextual>> {
+    let Some((position, contextual_args)) = contextual_args(attrs)? else {
+        return Ok(None);
+    };
+
+    let mut errors = darling::Error::accumulator();
+    for (idx, attr) in attrs.iter().enumerate() {
+        if idx != position && attr.path.is_ident("error") {
+            errors.push(
+                darling::Error::custom(
+                    "contextual errors are displayed according to `#[error(contextual)]`; \
+                     remove this attribute, or use `#[error(contextual, fmt = \"...\")]`",
+                )
+                .with_span(&attr.path),
+            );
+        }
+    }
+
+    let source = if contextual_args.none.is_present() {
+        if !fields.is_empty() {
+            errors.push(
+                darling::Error::custom(
+                    "`none` errors carry only their context, so must not have any fields",
+                )
+                .with_span(fields),
+            );
+        }
+        None
+    } else if let Fields::Unit = fields {
+        // A unit-like struct or variant carries only its context.
+        None
+    } else {
+        errors.handle(source_field(fields, ident))
+    };
+    errors.finish()?;
+
+    let (inner, source_name) = match source {
+        Some((inner, source_name)) => (Some(inner), source_name),
+        None => (None, None),
+    };
+
+    let mut kinds = Vec::new();
+    if inner.is_some() {
+        kinds.push(FieldKind::Source);
+    }
+    kinds.push(FieldKind::Context);
+    if args.location.is_present() {
+        kinds.push(FieldKind::Location);
+    }
+    if args.backtrace.is_present() || contextual_args.backtrace.is_present() {
+        kinds.push(FieldKind::Backtrace);
+    }
+
+    let contextual = Contextual {
+        path,
+        inner,
+        none: contextual_args.none.is_present(),
+        source_name,
+        fields: kinds,
+        ident: ident.clone(),
+    };
+    contextual.rewrite_fields(args, fields);
+    let template = contextual_args.fmt.unwrap_or_else(|| {
+        let has_source = contextual.inner.is_some();
+        let display = args.display.unwrap_or_default();
+        LitStr::new(display.template(has_source), Span::call_site())
+    });
+    let format = contextual.format(&template);
+    attrs[position] = parse_quote!(#[error(#format)]);
+    Ok(Some(contextual))
 }
 
-fn derive_for_enum(args: Args, item: ItemEnum) -> TokenStream {
-    todo!()
+/// Replace the argument names of placeholders in a format string.
+///
+/// `replace` is called with the name of each named placeholder, and returns its replacement, or
+/// `None` to leave it unchanged. Escaped braces and format specs are preserved.
+fn replace_placeholders(format: &str, replace: impl Fn(&str) -> Option<String>) -> String {
+    let mut out = String::with_capacity(format.len());
+    let mut rest = format;
+    while let Some(open) = rest.find('{') {
+        out.push_str(&rest[..open]);
+        rest = &rest[open..];
+        if rest.starts_with("{{") {
+            out.push_str("{{");
+            rest = &rest[2..];
+            continue;
+        }
+        let Some(close) = rest.find('}') else {
+            break;
+        };
+        let placeholder = &rest[1..close];
+        let (name, spec) = match placeholder.find(':') {
+            Some(colon) => placeholder.split_at(colon),
+            None => (placeholder, ""),
+        };
+        out.push('{');
+        out.push_str(&replace(name).unwrap_or_else(|| name.to_owned()));
+        out.push_str(spec);
+        out.push('}');
+        rest = &rest[close + 1..];
+    }
+    out.push_str(rest);
+    out
 }
 
-fn derive_for_struct(args: Args, item: ItemStruct) -> TokenStream {
-    todo!()
+/// Find the single field of a contextual struct or variant named `ident`, which holds the source
+/// error.
+///
+/// Returns its type, and its name if it is named.
+fn source_field(fields: &Fields, ident: &Ident) -> darling::Result<(Type, Option<Ident>)> {
+    match fields.len() {
+        1 => {}
+        0 => {
+            return Err(darling::Error::custom(format!(
+                "contextual error `{ident}` needs a field holding the source error\n\
+                 help: for `{ident}` to carry only its context, remove the empty fields"
+            ))
+            .with_span(fields));
+        }
+        _ => {
+            let extra = fields.iter().skip(1).collect::<Vec<_>>();
+            return Err(darling::Error::custom(
+                "contextual errors must have exactly one field, holding the source error; \
+                 `derive_context_err` adds the context field itself",
+            )
+            .with_span(&quote!(#( #extra )*)));
+        }
+    }
+    let field = fields.iter().next().expect("there is exactly one field");
+
+    let mut errors = darling::Error::accumulator();
+    for attr in &field.attrs {
+        let message = if attr.path.is_ident("source") || attr.path.is_ident("backtrace") {
+            "`derive_context_err` marks this field as the source itself; remove this attribute"
+        } else if attr.path.is_ident("from") {
+            "`#[from]` would convert the source error without any context; remove this attribute"
+        } else {
+            continue;
+        };
+        errors.push(darling::Error::custom(message).with_span(&attr.path));
+    }
+
+    if let Some(field_ident) = &field.ident {
+        let reserved = [
+            FieldKind::Context.binding(),
+            FieldKind::Location.binding(),
+            FieldKind::Backtrace.binding(),
+        ];
+        if reserved.contains(field_ident) {
+            errors.push(
+                darling::Error::custom(format!(
+                    "the `{field_ident}` field is added by `derive_context_err`; \
+                     rename this field"
+                ))
+                .with_span(field_ident),
+            );
+        }
+    }
+
+    errors.finish_with((field.ty.clone(), field.ident.clone()))
+}
+
+/// Generate a dedicated context trait, whose implementations convert a source error into `error`.
+///
+/// This mirrors `context_err::Context`, but is specific to a single error type.
+fn generate_context_trait(args: &Args, trait_ident: &Ident, error: &ErrorType) -> TokenStream2 {
+    let error_ty = error.ty();
+    let generics = error.generics;
+    let where_clause = &generics.where_clause;
+    let context_kv = args.structured.is_present().then(|| {
+        quote! {
+            fn context_kv<__S, __I, __V>(self, s: __S, fields: __I) -> ::core::result::Result<Self::Ok, #error_ty>
+            where
+                __S: ::std::string::ToString,
+                __I: ::core::iter::IntoIterator<Item = (&'static str, __V)>,
+                __V: ::core::convert::Into<::context_err::Value>;
+        }
+    });
+
+    // By default the trait is exactly as visible as the error type it produces.
+    let vis = args.trait_vis.as_ref().unwrap_or(error.vis);
+    let (method, with_method) = args.method_idents();
+    let context_type = args.context_type();
+    quote! {
+        #vis trait #trait_ident #generics #where_clause {
+            type Ok;
+            fn #method<__S>(self, s: __S) -> ::core::result::Result<Self::Ok, #error_ty>
+            where
+                __S: ::context_err::IntoContext<#context_type>;
+            fn #with_method<__F, __S>(self, f: __F) -> ::core::result::Result<Self::Ok, #error_ty>
+            where
+                __F: ::core::ops::FnOnce() -> __S,
+                __S: ::context_err::IntoContext<#context_type>;
+            #context_kv
+        }
+    }
+}
+
+/// Implement a context trait for results whose error type is the contextual's inner type, or
+/// for options if it has none.
+///
+/// If no dedicated trait was requested, this implements `context_err::Context<Error>`, and
+/// `context_err::ContextKv<Error>` for structured errors. Otherwise it implements the dedicated
+/// trait generated by [`generate_context_trait`].
+fn context_impl(args: &Args, error: &ErrorType, contextual: &Contextual) -> TokenStream2 {
+    let error_ty = error.ty();
+    let (method, with_method) = args.method_idents();
+    let impl_generics = error.impl_generics();
+    let (impl_generics, _, where_clause) = impl_generics.split_for_impl();
+    let (_, trait_generics, _) = error.generics.split_for_impl();
+    let construct = contextual.construct();
+    let source = match &contextual.inner {
+        Some(inner) => quote!(::core::result::Result<__T, #inner>),
+        None => quote!(::core::option::Option<__T>),
+    };
+
+    // The location must be captured outside the closure, or it would point into this impl.
+    let (track_caller, capture_location) = if args.location.is_present() {
+        (
+            quote!(#[track_caller]),
+            quote!(let location = ::core::panic::Location::caller();),
+        )
+    } else {
+        (quote!(), quote!())
+    };
+
+    // The backtrace is captured inside the closure, so it costs nothing on the happy path.
+    let capture_backtrace = contextual
+        .fields
+        .contains(&FieldKind::Backtrace)
+        .then(|| quote!(let backtrace = ::std::backtrace::Backtrace::capture();));
+
+    let convert = |context: TokenStream2| match &contextual.inner {
+        Some(_) => quote! {
+            #capture_location
+            self.map_err(|inner| {
+                let context = #context;
+                #capture_backtrace
+                #construct
+            })
+        },
+        None => quote! {
+            #capture_location
+            self.ok_or_else(|| {
+                let context = #context;
+                #capture_backtrace
+                #construct
+            })
+        },
+    };
+    let context = convert(args.make_context(quote!(s)));
+    let with_context = convert(args.make_context(quote!(f())));
+
+    let mut context_kv = args.structured.is_present().then(|| {
+        let context = convert(quote! {
+            ::context_err::StructuredContext::new(s).with_fields(fields)
+        });
+        quote! {
+            #track_caller
+            fn context_kv<__S, __I, __V>(self, s: __S, fields: __I) -> ::core::result::Result<__T, #error_ty>
+            where
+                __S: ::std::string::ToString,
+                __I: ::core::iter::IntoIterator<Item = (&'static str, __V)>,
+                __V: ::core::convert::Into<::context_err::Value>,
+            {
+                #context
+            }
+        }
+    });
+
+    let context_type = args.context_type();
+    let (trait_path, context_type_item, context_kv_impl) = match args.trait_ident() {
+        Some(trait_ident) => (quote!(#trait_ident #trait_generics), None, None),
+        None => {
+            let context_kv_impl = context_kv.take().map(|context_kv| {
+                quote! {
+                    impl #impl_generics ::context_err::ContextKv<#error_ty> for #source #where_clause {
+                        #context_kv
+                    }
+                }
+            });
+            (
+                quote!(::context_err::Context<#error_ty>),
+                Some(quote!(type ContextType = #context_type;)),
+                context_kv_impl,
+            )
+        }
+    };
+
+    quote! {
+        impl #impl_generics #trait_path for #source #where_clause {
+            type Ok = __T;
+            #context_type_item
+            #track_caller
+            fn #method<__S>(self, s: __S) -> ::core::result::Result<__T, #error_ty>
+            where
+                __S: ::context_err::IntoContext<#context_type>,
+            {
+                #context
+            }
+            #track_caller
+            fn #with_method<__F, __S>(self, f: __F) -> ::core::result::Result<__T, #error_ty>
+            where
+                __F: ::core::ops::FnOnce() -> __S,
+                __S: ::context_err::IntoContext<#context_type>,
+            {
+                #with_context
+            }
+            #context_kv
+        }
+        #context_kv_impl
+    }
+}
+
+/// The name of the constructor of a variant which carries only its context: the variant's name
+/// in snake case, which is a raw identifier if it is a keyword.
+fn constructor_ident(variant: &Ident) -> Ident {
+    let name = snake_case(&variant.to_string());
+    syn::parse_str::<Ident>(&name).unwrap_or_else(|_| Ident::new_raw(&name, Span::call_site()))
+}
+
+/// Generate a constructor for each contextual struct or variant which carries only its context.
+///
+/// The constructor of a struct is `new`; that of a variant is its name in snake case.
+fn message_constructors(
+    args: &Args,
+    error: &ErrorType,
+    contextuals: &[Contextual],
+    is_struct: bool,
+) -> TokenStream2 {
+    let error_ty = error.ty();
+    let vis = error.vis;
+    let (impl_generics, _, where_clause) = error.generics.split_for_impl();
+    let context_type = args.context_type();
+    let context = args.make_context(quote!(s));
+    let capture_location = args
+        .location
+        .is_present()
+        .then(|| quote!(let location = ::core::panic::Location::caller();));
+
+    let constructors = contextuals
+        .iter()
+        .filter(|contextual| contextual.inner.is_none())
+        .map(|contextual| {
+            let ident = &contextual.ident;
+            let (name, doc) = if is_struct {
+                (
+                    format_ident!("new"),
+                    "Construct this error from the given context.".to_owned(),
+                )
+            } else {
+                (
+                    constructor_ident(ident),
+                    format!("Construct a `{ident}` error from the given context."),
+                )
+            };
+            let capture_backtrace = contextual
+                .fields
+                .contains(&FieldKind::Backtrace)
+                .then(|| quote!(let backtrace = ::std::backtrace::Backtrace::capture();));
+            let construct = contextual.construct();
+            quote! {
+                #[doc = #doc]
+                #[track_caller]
+                #vis fn #name<__S>(s: __S) -> Self
+                where
+                    __S: ::context_err::IntoContext<#context_type>,
+                {
+                    let context = #context;
+                    #capture_location
+                    #capture_backtrace
+                    #construct
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+    if constructors.is_empty() {
+        return quote!();
+    }
+
+    quote! {
+        impl #impl_generics #error_ty #where_clause {
+            #( #constructors )*
+        }
+    }
+}
+
+/// Generate the `location` accessor, which returns where context was added, if it was.
+fn location_accessor(error: &ErrorType, contextuals: &[Contextual]) -> TokenStream2 {
+    let error_ty = error.ty();
+    let (impl_generics, _, where_clause) = error.generics.split_for_impl();
+    let patterns = contextuals
+        .iter()
+        .map(|contextual| contextual.pattern(&[FieldKind::Location]));
+    let location = FieldKind::Location.binding();
+
+    quote! {
+        impl #impl_generics #error_ty #where_clause {
+            /// The location at which context was added to this error.
+            ///
+            /// This is `None` if this error is not contextual.
+            pub fn location(&self) -> ::core::option::Option<&'static ::core::panic::Location<'static>> {
+                #[allow(unreachable_patterns)]
+                match self {
+                    #( #patterns => ::core::option::Option::Some(#location), )*
+                    _ => ::core::option::Option::None,
+                }
+            }
+        }
+    }
+}
+
+/// Generate the `backtrace` accessor, which returns the backtrace captured when context was added.
+fn backtrace_accessor(error: &ErrorType, contextuals: &[Contextual]) -> TokenStream2 {
+    let error_ty = error.ty();
+    let (impl_generics, _, where_clause) = error.generics.split_for_impl();
+    let patterns = contextuals
+        .iter()
+        .filter(|contextual| contextual.fields.contains(&FieldKind::Backtrace))
+        .map(|contextual| contextual.pattern(&[FieldKind::Backtrace]));
+    let backtrace = FieldKind::Backtrace.binding();
+
+    quote! {
+        impl #impl_generics #error_ty #where_clause {
+            /// The backtrace captured when context was added to this error.
+            ///
+            /// This is `None` if this error is not contextual, or does not capture a backtrace.
+            pub fn backtrace(&self) -> ::core::option::Option<&::std::backtrace::Backtrace> {
+                #[allow(unreachable_patterns)]
+                match self {
+                    #( #patterns => ::core::option::Option::Some(#backtrace), )*
+                    _ => ::core::option::Option::None,
+                }
+            }
+        }
+    }
+}
+
+/// Generate the `context_fields` accessor, which returns the fields of a structured context.
+fn fields_accessor(error: &ErrorType, contextuals: &[Contextual]) -> TokenStream2 {
+    let error_ty = error.ty();
+    let (impl_generics, _, where_clause) = error.generics.split_for_impl();
+    let patterns = contextuals
+        .iter()
+        .map(|contextual| contextual.pattern(&[FieldKind::Context]));
+    let context = FieldKind::Context.binding();
+
+    quote! {
+        impl #impl_generics #error_ty #where_clause {
+            /// The fields of the structured context added to this error.
+            ///
+            /// This is empty if this error is not contextual.
+            pub fn context_fields(&self) -> &[(&'static str, ::context_err::Value)] {
+                #[allow(unreachable_patterns)]
+                match self {
+                    #( #patterns => #context.fields(), )*
+                    _ => &[],
+                }
+            }
+        }
+    }
+}
+
+/// Implement `context_err::ContextualError`, which exposes the context and source of contextual
+/// structs and variants uniformly.
+fn contextual_error_impl(
+    args: &Args,
+    error: &ErrorType,
+    contextuals: &[Contextual],
+) -> TokenStream2 {
+    let error_ty = error.ty();
+    // `thiserror` infers bounds for its `Error` impl, such as `T: Display` for a type parameter in
+    // a format string, so we require whatever it does rather than the type's own bounds alone.
+    let mut generics = error.generics.clone();
+    let where_clause = generics.make_where_clause();
+    where_clause
+        .predicates
+        .push(parse_quote!(Self: ::std::error::Error));
+    for inner in contextuals
+        .iter()
+        .filter_map(|contextual| contextual.inner.as_ref())
+    {
+        where_clause
+            .predicates
+            .push(parse_quote!(#inner: ::std::error::Error + 'static));
+    }
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    let context_patterns = contextuals
+        .iter()
+        .map(|contextual| contextual.pattern(&[FieldKind::Context]));
+    let context = args.context_str(FieldKind::Context.binding().to_token_stream());
+    let source_patterns = contextuals
+        .iter()
+        .filter(|contextual| contextual.inner.is_some())
+        .map(|contextual| contextual.pattern(&[FieldKind::Source]));
+    let source = FieldKind::Source.binding();
+
+    quote! {
+        impl #impl_generics ::context_err::ContextualError for #error_ty #where_clause {
+            fn context(&self) -> ::core::option::Option<&str> {
+                #[allow(unreachable_patterns)]
+                match self {
+                    #( #context_patterns => ::core::option::Option::Some(#context), )*
+                    _ => ::core::option::Option::None,
+                }
+            }
+
+            fn source_ref(&self) -> ::core::option::Option<&(dyn ::std::error::Error + 'static)> {
+                #[allow(unreachable_patterns)]
+                match self {
+                    #( #source_patterns => ::core::option::Option::Some(#source), )*
+                    _ => ::core::option::Option::None,
+                }
+            }
+        }
+    }
+}
+
+/// Generate the `is_*`, `as_*`, and `into_*` accessors for each contextual variant, so that
+/// callers needn't know the layout of rewritten variants.
+fn variant_accessors(args: &Args, error: &ErrorType, contextuals: &[Contextual]) -> TokenStream2 {
+    let error_ty = error.ty();
+    let (impl_generics, _, where_clause) = error.generics.split_for_impl();
+    let context_type = args.context_type();
+
+    let accessors = contextuals.iter().map(|contextual| {
+        let variant = &contextual.ident;
+        let name = snake_case(&variant.to_string());
+        let is = format_ident!("is_{}", name);
+        let as_ = format_ident!("as_{}", name);
+        let into = format_ident!("into_{}", name);
+
+        let any = contextual.pattern(&[]);
+        let context = FieldKind::Context.binding();
+        let (context_ref_type, context_ref) = args.context_ref(context.to_token_stream());
+        let (as_type, as_pattern, as_value, into_type, into_pattern, into_value) =
+            match &contextual.inner {
+                Some(inner) => {
+                    let source = FieldKind::Source.binding();
+                    (
+                        quote!((&#inner, #context_ref_type)),
+                        contextual.pattern(&[FieldKind::Source, FieldKind::Context]),
+                        quote!((#source, #context_ref)),
+                        inner.to_token_stream(),
+                        contextual.pattern(&[FieldKind::Source]),
+                        source.to_token_stream(),
+                    )
+                }
+                None => {
+                    let pattern = contextual.pattern(&[FieldKind::Context]);
+                    (
+                        context_ref_type,
+                        pattern.clone(),
+                        context_ref,
+                        context_type.clone(),
+                        pattern,
+                        context.to_token_stream(),
+                    )
+                }
+            };
+
+        let is_doc = format!("Whether this is a `{variant}` error.");
+        let as_doc = match contextual.inner {
+            Some(_) => format!("The source error and context of a `{variant}` error."),
+            None => format!("The context of a `{variant}` error."),
+        };
+        let into_doc = match contextual.inner {
+            Some(_) => {
+                format!("Extract the source error of a `{variant}` error, or return `self`.")
+            }
+            None => format!("Extract the context of a `{variant}` error, or return `self`."),
+        };
+
+        quote! {
+            #[doc = #is_doc]
+            pub fn #is(&self) -> bool {
+                ::core::matches!(self, #any)
+            }
+
+            #[doc = #as_doc]
+            pub fn #as_(&self) -> ::core::option::Option<#as_type> {
+                #[allow(unreachable_patterns)]
+                match self {
+                    #as_pattern => ::core::option::Option::Some(#as_value),
+                    _ => ::core::option::Option::None,
+                }
+            }
+
+            #[doc = #into_doc]
+            pub fn #into(self) -> ::core::result::Result<#into_type, Self> {
+                #[allow(unreachable_patterns)]
+                match self {
+                    #into_pattern => ::core::result::Result::Ok(#into_value),
+                    _ => ::core::result::Result::Err(self),
+                }
+            }
+        }
+    });
+
+    quote! {
+        impl #impl_generics #error_ty #where_clause {
+            #( #accessors )*
+        }
+    }
+}
+
+/// Convert a variant name in `UpperCamelCase` to `snake_case`, treating runs of capitals as
+/// acronyms: `ParseInt` becomes `parse_int`, and `HTTPError` becomes `http_error`.
+fn snake_case(name: &str) -> String {
+    let chars = name.chars().collect::<Vec<_>>();
+    let mut snake = String::new();
+    for (idx, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() && idx > 0 {
+            let prev = chars[idx - 1];
+            let next_is_lower = chars.get(idx + 1).is_some_and(|next| next.is_lowercase());
+            if prev != '_' && (!prev.is_uppercase() || next_is_lower) {
+                snake.push('_');
+            }
+        }
+        snake.extend(c.to_lowercase());
+    }
+    snake
+}
+
+/// Generate the `ErrorKind` enum, which has a fieldless variant for each variant of `Error`, and
+/// the `Error::kind` method.
+fn kind_enum(error: &ErrorType, item: &ItemEnum) -> TokenStream2 {
+    let vis = error.vis;
+    let error_ident = error.ident;
+    let error_ty = error.ty();
+    let (impl_generics, _, where_clause) = error.generics.split_for_impl();
+    let kind = format_ident!("{}Kind", error_ident);
+
+    let variants = item
+        .variants
+        .iter()
+        .map(|variant| {
+            let ident = &variant.ident;
+            let attrs = variant
+                .attrs
+                .iter()
+                .filter(|attr| attr.path.is_ident("cfg") || attr.path.is_ident("doc"))
+                .collect::<Vec<_>>();
+            quote!(#( #attrs )* #ident)
+        })
+        .collect::<Vec<_>>();
+    let arms = item.variants.iter().map(|variant| {
+        let ident = &variant.ident;
+        let cfgs = variant
+            .attrs
+            .iter()
+            .filter(|attr| attr.path.is_ident("cfg"));
+        quote!(#( #cfgs )* Self::#ident { .. } => #kind::#ident,)
+    });
+
+    let kind_doc = format!("The kind of an [`{error_ident}`], without any of its fields.");
+    let method_doc = format!("The kind of this error, as an [`{kind}`].");
+    quote! {
+        #[doc = #kind_doc]
+        #[non_exhaustive]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        #vis enum #kind {
+            #( #variants, )*
+        }
+
+        impl #impl_generics #error_ty #where_clause {
+            #[doc = #method_doc]
+            pub fn kind(&self) -> #kind {
+                match *self {
+                    #( #arms )*
+                }
+            }
+        }
+    }
 }
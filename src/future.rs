@@ -0,0 +1,119 @@
+use std::{
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    task::{self, ready, Poll},
+};
+
+use pin_project_lite::pin_project;
+
+use crate::{Context, IntoContext};
+
+/// Add context to the output of a future, converting it into the error type `E`.
+///
+/// This is implemented for every future whose output implements [`Context<E>`], so that context
+/// can be attached to futures before they are passed to combinators such as `join_all` or
+/// `select`, rather than only after they are awaited.
+///
+/// Locations recorded for error types declared with `location` point at the point at which the
+/// future completes, rather than at the call to `.context`; to record the call site, await the
+/// future first, and add context to its output.
+pub trait FutureContextErr<E>: Future + Sized
+where
+    Self::Output: Context<E>,
+{
+    /// Wrap the error, if any, with the given context once the future completes.
+    fn context<S>(self, s: S) -> ContextFuture<Self, S, E>
+    where
+        S: IntoContext<<Self::Output as Context<E>>::ContextType>,
+    {
+        ContextFuture {
+            future: self,
+            context: Some(s),
+            error: PhantomData,
+        }
+    }
+
+    /// Wrap the error, if any, with context produced by `f` once the future completes.
+    ///
+    /// `f` is only called if there is actually an error.
+    fn with_context<F, S>(self, f: F) -> WithContextFuture<Self, F, E>
+    where
+        F: FnOnce() -> S,
+        S: IntoContext<<Self::Output as Context<E>>::ContextType>,
+    {
+        WithContextFuture {
+            future: self,
+            f: Some(f),
+            error: PhantomData,
+        }
+    }
+}
+
+impl<Fut, E> FutureContextErr<E> for Fut
+where
+    Fut: Future,
+    Fut::Output: Context<E>,
+{
+}
+
+pin_project! {
+    /// The future returned by [`FutureContextErr::context`].
+    #[must_use = "futures do nothing unless polled"]
+    pub struct ContextFuture<Fut, S, E> {
+        #[pin]
+        future: Fut,
+        context: Option<S>,
+        error: PhantomData<fn() -> E>,
+    }
+}
+
+impl<Fut, S, E> Future for ContextFuture<Fut, S, E>
+where
+    Fut: Future,
+    Fut::Output: Context<E>,
+    S: IntoContext<<Fut::Output as Context<E>>::ContextType>,
+{
+    type Output = Result<<Fut::Output as Context<E>>::Ok, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let output = ready!(this.future.poll(cx));
+        let context = this
+            .context
+            .take()
+            .expect("ContextFuture polled after completion");
+        Poll::Ready(output.context(context))
+    }
+}
+
+pin_project! {
+    /// The future returned by [`FutureContextErr::with_context`].
+    #[must_use = "futures do nothing unless polled"]
+    pub struct WithContextFuture<Fut, F, E> {
+        #[pin]
+        future: Fut,
+        f: Option<F>,
+        error: PhantomData<fn() -> E>,
+    }
+}
+
+impl<Fut, F, S, E> Future for WithContextFuture<Fut, F, E>
+where
+    Fut: Future,
+    Fut::Output: Context<E>,
+    F: FnOnce() -> S,
+    S: IntoContext<<Fut::Output as Context<E>>::ContextType>,
+{
+    type Output = Result<<Fut::Output as Context<E>>::Ok, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let output = ready!(this.future.poll(cx));
+        let f = this
+            .f
+            .take()
+            .expect("WithContextFuture polled after completion");
+        Poll::Ready(output.with_context(f))
+    }
+}
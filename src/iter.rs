@@ -0,0 +1,101 @@
+use std::marker::PhantomData;
+
+use crate::{Context, IntoContext};
+
+/// Add context to each item of an iterator, converting errors into the error type `E`.
+///
+/// This is implemented for every iterator whose items implement [`Context<E>`], such as
+/// iterators of `Result`s whose error type is a contextual source type of `E`.
+pub trait IteratorContextErr<E>: Iterator + Sized
+where
+    Self::Item: Context<E>,
+{
+    /// Wrap each error, if any, with a clone of the given context.
+    fn context<S>(self, s: S) -> ContextIter<Self, S, E>
+    where
+        S: Clone + IntoContext<<Self::Item as Context<E>>::ContextType>,
+    {
+        ContextIter {
+            iter: self,
+            context: s,
+            error: PhantomData,
+        }
+    }
+
+    /// Wrap each error, if any, with context produced by `f`.
+    ///
+    /// `f` is called once for each error, and not at all for other items.
+    fn with_context<F, S>(self, f: F) -> WithContextIter<Self, F, E>
+    where
+        F: FnMut() -> S,
+        S: IntoContext<<Self::Item as Context<E>>::ContextType>,
+    {
+        WithContextIter {
+            iter: self,
+            f,
+            error: PhantomData,
+        }
+    }
+}
+
+impl<I, E> IteratorContextErr<E> for I
+where
+    I: Iterator,
+    I::Item: Context<E>,
+{
+}
+
+/// The iterator returned by [`IteratorContextErr::context`].
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct ContextIter<I, S, E> {
+    iter: I,
+    context: S,
+    error: PhantomData<fn() -> E>,
+}
+
+impl<I, S, E> Iterator for ContextIter<I, S, E>
+where
+    I: Iterator,
+    I::Item: Context<E>,
+    S: Clone + IntoContext<<I::Item as Context<E>>::ContextType>,
+{
+    type Item = Result<<I::Item as Context<E>>::Ok, E>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let context = &self.context;
+        self.iter
+            .next()
+            .map(|item| item.with_context(|| context.clone()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+/// The iterator returned by [`IteratorContextErr::with_context`].
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct WithContextIter<I, F, E> {
+    iter: I,
+    f: F,
+    error: PhantomData<fn() -> E>,
+}
+
+impl<I, F, S, E> Iterator for WithContextIter<I, F, E>
+where
+    I: Iterator,
+    I::Item: Context<E>,
+    F: FnMut() -> S,
+    S: IntoContext<<I::Item as Context<E>>::ContextType>,
+{
+    type Item = Result<<I::Item as Context<E>>::Ok, E>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let f = &mut self.f;
+        self.iter.next().map(|item| item.with_context(f))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
@@ -1,3 +1,313 @@
-pub use context_err_derive::derive_context_err;
+mod future;
+mod iter;
+mod report;
+#[cfg(feature = "futures")]
+mod stream;
+mod structured;
+
+use std::{borrow::Cow, error::Error, sync::Arc};
+
+pub use context_err_derive::{context, derive_context_err};
+pub use future::{ContextFuture, FutureContextErr, WithContextFuture};
+pub use iter::{ContextIter, IteratorContextErr, WithContextIter};
+pub use report::Report;
+#[cfg(feature = "futures")]
+pub use stream::{ContextStream, StreamContextErr, WithContextStream};
+pub use structured::{StructuredContext, Value};
 /// Reexporting `thiserror` means that users of `context-err` don't need to also depend on it separately.
 pub use thiserror;
+
+/// Add context to a fallible value, converting it into the error type `E`.
+///
+/// `derive_context_err` implements this trait for `Result<T, Inner>` for each contextual source
+/// type `Inner` of `E`, and for `Option<T>` if `E` has a `none` variant.
+///
+/// Because the target error type is a parameter of the trait rather than of the implementing
+/// type, several error types may wrap the same source type without conflict. The target is then
+/// inferred from context, for example from the return type of the enclosing function.
+pub trait Context<E> {
+    type Ok;
+    /// The type in which `E` stores its context.
+    ///
+    /// This is `String` unless the error type was declared with a different `context_type`.
+    type ContextType;
+
+    /// Wrap the error, if any, with the given context.
+    fn context<S>(self, s: S) -> Result<Self::Ok, E>
+    where
+        S: IntoContext<Self::ContextType>;
+
+    /// Wrap the error, if any, with context produced by `f`.
+    ///
+    /// `f` is only called if there is actually an error.
+    fn with_context<F, S>(self, f: F) -> Result<Self::Ok, E>
+    where
+        F: FnOnce() -> S,
+        S: IntoContext<Self::ContextType>;
+}
+
+/// Conversion into the type `C` in which an error type stores its context.
+///
+/// Anything which implements `ToString` can become a `String` or a [`StructuredContext`].
+/// The cheaper context types accept only what they can store without formatting: string slices
+/// and `String`s for `Cow<'static, str>`, `Box<str>`, and `Arc<str>`, and only string literals
+/// for `&'static str`.
+pub trait IntoContext<C> {
+    fn into_context(self) -> C;
+}
+
+impl<S> IntoContext<String> for S
+where
+    S: ToString,
+{
+    fn into_context(self) -> String {
+        self.to_string()
+    }
+}
+
+impl<S> IntoContext<StructuredContext> for S
+where
+    S: ToString,
+{
+    fn into_context(self) -> StructuredContext {
+        StructuredContext::new(self)
+    }
+}
+
+impl IntoContext<&'static str> for &'static str {
+    fn into_context(self) -> &'static str {
+        self
+    }
+}
+
+impl<S> IntoContext<Cow<'static, str>> for S
+where
+    S: Into<Cow<'static, str>>,
+{
+    fn into_context(self) -> Cow<'static, str> {
+        self.into()
+    }
+}
+
+impl<S> IntoContext<Box<str>> for S
+where
+    S: Into<Box<str>>,
+{
+    fn into_context(self) -> Box<str> {
+        self.into()
+    }
+}
+
+impl<S> IntoContext<Arc<str>> for S
+where
+    S: Into<Arc<str>>,
+{
+    fn into_context(self) -> Arc<str> {
+        self.into()
+    }
+}
+
+/// Add structured context to a fallible value, converting it into the error type `E`.
+///
+/// `derive_context_err` implements this trait alongside [`Context`] for error types declared with
+/// `#[derive_context_err(structured)]`, whose context is a [`StructuredContext`].
+pub trait ContextKv<E>: Context<E> {
+    /// Wrap the error, if any, with the given message and fields.
+    fn context_kv<S, I, V>(self, s: S, fields: I) -> Result<Self::Ok, E>
+    where
+        S: ToString,
+        I: IntoIterator<Item = (&'static str, V)>,
+        V: Into<Value>;
+}
+
+/// Uniform access to the context of any error type declared with `derive_context_err`.
+///
+/// `derive_context_err` implements this trait for every error type, so that generic code, such
+/// as logging middleware, can extract context without matching on each variant.
+pub trait ContextualError: Error {
+    /// The context added to this error, or `None` if this error is not contextual.
+    ///
+    /// For errors declared `structured`, this is the message without any fields.
+    fn context(&self) -> Option<&str>;
+
+    /// The source error to which context was added, or `None` if this error is not contextual or
+    /// carries only its context.
+    fn source_ref(&self) -> Option<&(dyn Error + 'static)>;
+}
+
+/// Return early with an error built from a variant which carries only its context.
+///
+/// The first argument is the path of the variant, such as `Error::Missing`, and the rest are
+/// the arguments of `format!`. If the error type records locations or backtraces, they are
+/// captured here. The error is converted with `From`, like with `?`.
+///
+/// ```ignore
+/// bail!(Error::Missing, "no user with id {id}");
+/// ```
+#[macro_export]
+macro_rules! bail {
+    ($variant:path, $($arg:tt)+) => {
+        return ::core::result::Result::Err(::core::convert::From::from(
+            $crate::__private::construct($variant, ::std::format!($($arg)+)),
+        ))
+    };
+}
+
+/// Return early with an error built from a variant which carries only its context, unless a
+/// condition holds.
+///
+/// This is the same as [`bail!`], but only if `cond` is false.
+///
+/// ```ignore
+/// ensure!(count <= limit, Error::Invalid, "{count} items exceeds the limit of {limit}");
+/// ```
+#[macro_export]
+macro_rules! ensure {
+    ($cond:expr, $variant:path, $($arg:tt)+) => {
+        if !$cond {
+            $crate::bail!($variant, $($arg)+);
+        }
+    };
+}
+
+/// Support for code generated by `#[context]`, `bail!`, and `ensure!`. Not public API.
+#[doc(hidden)]
+pub mod __private {
+    use std::{backtrace::Backtrace, cell::Cell, marker::PhantomData, panic::Location};
+
+    use crate::{Context, IntoContext};
+
+    /// The operand of a `?` in a function annotated with `#[context]`.
+    ///
+    /// Method resolution picks [`AddContext`] if the operand implements `Context<E>`, and falls
+    /// back to [`PassThrough`] otherwise, which leaves it for `?` to convert as usual. Whether the
+    /// formatted context can be stored is checked only once [`AddContext`] has been picked, so
+    /// that an error type with an incompatible context type is rejected rather than passed
+    /// through.
+    pub struct ContextWrap<R, E> {
+        result: Cell<Option<R>>,
+        error: PhantomData<fn() -> E>,
+    }
+
+    impl<R, E> ContextWrap<R, E> {
+        pub fn new(result: R) -> Self {
+            ContextWrap {
+                result: Cell::new(Some(result)),
+                error: PhantomData,
+            }
+        }
+
+        fn take(&self) -> R {
+            self.result
+                .take()
+                .expect("context is only added to a result once")
+        }
+    }
+
+    pub trait AddContext {
+        type Output;
+        type ContextType;
+        fn __context<F>(&self, f: F) -> Self::Output
+        where
+            F: FnOnce() -> String,
+            String: IntoContext<Self::ContextType>;
+    }
+
+    impl<R, E> AddContext for ContextWrap<R, E>
+    where
+        R: Context<E>,
+    {
+        type Output = Result<R::Ok, E>;
+        type ContextType = R::ContextType;
+
+        #[track_caller]
+        fn __context<F>(&self, f: F) -> Self::Output
+        where
+            F: FnOnce() -> String,
+            String: IntoContext<Self::ContextType>,
+        {
+            self.take().with_context(f)
+        }
+    }
+
+    pub trait PassThrough {
+        type Output;
+        fn __context<F>(&self, f: F) -> Self::Output
+        where
+            F: FnOnce() -> String;
+    }
+
+    impl<R, E> PassThrough for &ContextWrap<R, E> {
+        type Output = R;
+
+        fn __context<F>(&self, _: F) -> Self::Output
+        where
+            F: FnOnce() -> String,
+        {
+            self.take()
+        }
+    }
+
+    /// Build an error from the constructor of a variant which carries only its context.
+    #[track_caller]
+    pub fn construct<F, Fields, E>(variant: F, context: String) -> E
+    where
+        F: ConstructContext<Fields, E>,
+    {
+        variant.construct(context)
+    }
+
+    /// The constructor of a variant which carries only its context, along with the location and
+    /// backtrace at which it was created if the error type records those.
+    ///
+    /// `Fields` is the tuple of the constructor's parameters, which distinguishes the impls.
+    pub trait ConstructContext<Fields, E> {
+        fn construct(self, context: String) -> E;
+    }
+
+    impl<F, C, E> ConstructContext<(C,), E> for F
+    where
+        F: FnOnce(C) -> E,
+        String: IntoContext<C>,
+    {
+        fn construct(self, context: String) -> E {
+            self(context.into_context())
+        }
+    }
+
+    impl<F, C, E> ConstructContext<(C, &'static Location<'static>), E> for F
+    where
+        F: FnOnce(C, &'static Location<'static>) -> E,
+        String: IntoContext<C>,
+    {
+        #[track_caller]
+        fn construct(self, context: String) -> E {
+            self(context.into_context(), Location::caller())
+        }
+    }
+
+    impl<F, C, E> ConstructContext<(C, Backtrace), E> for F
+    where
+        F: FnOnce(C, Backtrace) -> E,
+        String: IntoContext<C>,
+    {
+        fn construct(self, context: String) -> E {
+            self(context.into_context(), Backtrace::capture())
+        }
+    }
+
+    impl<F, C, E> ConstructContext<(C, &'static Location<'static>, Backtrace), E> for F
+    where
+        F: FnOnce(C, &'static Location<'static>, Backtrace) -> E,
+        String: IntoContext<C>,
+    {
+        #[track_caller]
+        fn construct(self, context: String) -> E {
+            self(
+                context.into_context(),
+                Location::caller(),
+                Backtrace::capture(),
+            )
+        }
+    }
+}
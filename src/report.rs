@@ -0,0 +1,127 @@
+use std::{
+    error::Error,
+    fmt,
+    process::{ExitCode, Termination},
+};
+
+/// An error along with the chain of its sources, formatted for people to read.
+///
+/// By default, a report displays the error on the first line, followed by each of its sources:
+///
+/// ```text
+/// loading settings
+///
+/// Caused by:
+///     0: reading config
+///     1: No such file or directory (os error 2)
+/// ```
+///
+/// A [compact](Report::compact) report displays the whole chain on a single line instead, as in
+/// `loading settings: reading config: No such file or directory (os error 2)`. A source is left
+/// out if the error before it already ends with it, as errors declared with `display = "chain"`
+/// do, so that it is not repeated.
+///
+/// `Debug` formats a report exactly like `Display`, so that returning
+/// `Result<(), Report<Error>>` from `main` prints the report rather than the `Debug` output of
+/// the error. Errors convert into reports with `?`.
+pub struct Report<E> {
+    error: E,
+    compact: bool,
+}
+
+impl<E> Report<E>
+where
+    E: Error,
+{
+    /// Create a multi-line report of `error`.
+    pub fn new(error: E) -> Self {
+        Report {
+            error,
+            compact: false,
+        }
+    }
+
+    /// Display the whole chain on a single line, with each source following a colon.
+    pub fn compact(mut self) -> Self {
+        self.compact = true;
+        self
+    }
+
+    /// The reported error.
+    pub fn error(&self) -> &E {
+        &self.error
+    }
+
+    /// Extract the reported error.
+    pub fn into_error(self) -> E {
+        self.error
+    }
+
+    fn sources(&self) -> impl Iterator<Item = &(dyn Error + 'static)> {
+        std::iter::successors(self.error.source(), |&source| source.source())
+    }
+}
+
+impl<E> From<E> for Report<E>
+where
+    E: Error,
+{
+    fn from(error: E) -> Self {
+        Report::new(error)
+    }
+}
+
+impl<E> fmt::Display for Report<E>
+where
+    E: Error,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.compact {
+            let mut previous = self.error.to_string();
+            f.write_str(&previous)?;
+            for source in self.sources() {
+                let source = source.to_string();
+                if !previous.ends_with(&source) {
+                    write!(f, ": {source}")?;
+                }
+                previous = source;
+            }
+            return Ok(());
+        }
+
+        write!(f, "{}", self.error)?;
+
+        let numbered = self.sources().nth(1).is_some();
+        for (idx, source) in self.sources().enumerate() {
+            if idx == 0 {
+                f.write_str("\n\nCaused by:")?;
+            }
+            if numbered {
+                write!(f, "\n    {idx}: {source}")?;
+            } else {
+                write!(f, "\n    {source}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<E> fmt::Debug for Report<E>
+where
+    E: Error,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+/// Returning a report from `main` prints it to stderr, and exits unsuccessfully.
+impl<E> Termination for Report<E>
+where
+    E: Error,
+{
+    fn report(self) -> ExitCode {
+        eprintln!("Error: {self}");
+        ExitCode::FAILURE
+    }
+}
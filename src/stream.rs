@@ -0,0 +1,116 @@
+use std::{
+    marker::PhantomData,
+    pin::Pin,
+    task::{self, ready, Poll},
+};
+
+use futures_core::Stream;
+use pin_project_lite::pin_project;
+
+use crate::{Context, IntoContext};
+
+/// Add context to each item of a stream, converting errors into the error type `E`.
+///
+/// This is the asynchronous counterpart of [`IteratorContextErr`](crate::IteratorContextErr),
+/// implemented for every stream whose items implement [`Context<E>`].
+pub trait StreamContextErr<E>: Stream + Sized
+where
+    Self::Item: Context<E>,
+{
+    /// Wrap each error, if any, with a clone of the given context.
+    fn context<S>(self, s: S) -> ContextStream<Self, S, E>
+    where
+        S: Clone + IntoContext<<Self::Item as Context<E>>::ContextType>,
+    {
+        ContextStream {
+            stream: self,
+            context: s,
+            error: PhantomData,
+        }
+    }
+
+    /// Wrap each error, if any, with context produced by `f`.
+    ///
+    /// `f` is called once for each error, and not at all for other items.
+    fn with_context<F, S>(self, f: F) -> WithContextStream<Self, F, E>
+    where
+        F: FnMut() -> S,
+        S: IntoContext<<Self::Item as Context<E>>::ContextType>,
+    {
+        WithContextStream {
+            stream: self,
+            f,
+            error: PhantomData,
+        }
+    }
+}
+
+impl<St, E> StreamContextErr<E> for St
+where
+    St: Stream,
+    St::Item: Context<E>,
+{
+}
+
+pin_project! {
+    /// The stream returned by [`StreamContextErr::context`].
+    #[must_use = "streams do nothing unless polled"]
+    pub struct ContextStream<St, S, E> {
+        #[pin]
+        stream: St,
+        context: S,
+        error: PhantomData<fn() -> E>,
+    }
+}
+
+impl<St, S, E> Stream for ContextStream<St, S, E>
+where
+    St: Stream,
+    St::Item: Context<E>,
+    S: Clone + IntoContext<<St::Item as Context<E>>::ContextType>,
+{
+    type Item = Result<<St::Item as Context<E>>::Ok, E>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        let context = &*this.context;
+        let item = ready!(this.stream.poll_next(cx));
+        Poll::Ready(item.map(|item| item.with_context(|| context.clone())))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.stream.size_hint()
+    }
+}
+
+pin_project! {
+    /// The stream returned by [`StreamContextErr::with_context`].
+    #[must_use = "streams do nothing unless polled"]
+    pub struct WithContextStream<St, F, E> {
+        #[pin]
+        stream: St,
+        f: F,
+        error: PhantomData<fn() -> E>,
+    }
+}
+
+impl<St, F, S, E> Stream for WithContextStream<St, F, E>
+where
+    St: Stream,
+    St::Item: Context<E>,
+    F: FnMut() -> S,
+    S: IntoContext<<St::Item as Context<E>>::ContextType>,
+{
+    type Item = Result<<St::Item as Context<E>>::Ok, E>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        let f = this.f;
+        let item = ready!(this.stream.poll_next(cx));
+        Poll::Ready(item.map(|item| item.with_context(f)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.stream.size_hint()
+    }
+}
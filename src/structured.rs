@@ -0,0 +1,145 @@
+use std::fmt;
+
+/// A context message along with an ordered list of named values.
+///
+/// This is the context type of error types declared with `#[derive_context_err(structured)]`.
+/// Its `Display` implementation renders the message followed by the fields, but the fields are
+/// also available programmatically, for example for structured logging.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructuredContext {
+    message: String,
+    fields: Vec<(&'static str, Value)>,
+}
+
+impl StructuredContext {
+    /// Create a structured context with the given message and no fields.
+    pub fn new<S>(message: S) -> Self
+    where
+        S: ToString,
+    {
+        StructuredContext {
+            message: message.to_string(),
+            fields: Vec::new(),
+        }
+    }
+
+    /// Append fields to this context.
+    pub fn with_fields<I, V>(mut self, fields: I) -> Self
+    where
+        I: IntoIterator<Item = (&'static str, V)>,
+        V: Into<Value>,
+    {
+        self.fields
+            .extend(fields.into_iter().map(|(key, value)| (key, value.into())));
+        self
+    }
+
+    /// The context message.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// All fields, in the order in which they were added.
+    pub fn fields(&self) -> &[(&'static str, Value)] {
+        &self.fields
+    }
+
+    /// The value of the first field named `key`, if any.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.fields
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, value)| value)
+    }
+}
+
+impl fmt::Display for StructuredContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)?;
+        if !self.fields.is_empty() {
+            f.write_str(" (")?;
+            for (idx, (key, value)) in self.fields.iter().enumerate() {
+                if idx > 0 {
+                    f.write_str(", ")?;
+                }
+                write!(f, "{key}={value}")?;
+            }
+            f.write_str(")")?;
+        }
+        Ok(())
+    }
+}
+
+/// The value of a field of a [`StructuredContext`].
+///
+/// Values of arbitrary types can be stored as strings with [`Value::display`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Bool(bool),
+    I64(i64),
+    U64(u64),
+    F64(f64),
+    String(String),
+}
+
+impl Value {
+    /// Store any displayable value as a string.
+    pub fn display<D>(value: D) -> Self
+    where
+        D: fmt::Display,
+    {
+        Value::String(value.to_string())
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Bool(value) => value.fmt(f),
+            Value::I64(value) => value.fmt(f),
+            Value::U64(value) => value.fmt(f),
+            Value::F64(value) => value.fmt(f),
+            Value::String(value) => value.fmt(f),
+        }
+    }
+}
+
+macro_rules! impl_from {
+    ($variant:ident($as:ty): $($from:ty),*) => {
+        $(
+            impl From<$from> for Value {
+                fn from(value: $from) -> Self {
+                    Value::$variant(value as $as)
+                }
+            }
+        )*
+    };
+}
+
+impl_from!(I64(i64): i8, i16, i32, i64, isize);
+impl_from!(U64(u64): u8, u16, u32, u64, usize);
+impl_from!(F64(f64): f32, f64);
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Bool(value)
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::String(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::String(value.to_owned())
+    }
+}
+
+impl From<&String> for Value {
+    fn from(value: &String) -> Self {
+        Value::String(value.clone())
+    }
+}
@@ -0,0 +1,91 @@
+//! Backtraces in errors need `error_generic_member_access`, so these tests only run on nightly:
+//!
+//! ```text
+//! RUSTFLAGS="--cfg context_err_nightly" cargo +nightly test --test backtrace
+//! ```
+
+#![cfg(context_err_nightly)]
+#![feature(error_generic_member_access)]
+
+use context_err::{bail, derive_context_err, Context};
+
+#[derive_context_err(backtrace, location)]
+#[derive(Debug)]
+pub enum Error {
+    #[error(contextual)]
+    Parse(std::num::ParseIntError),
+    #[error(contextual, none)]
+    Missing,
+    #[error("not contextual")]
+    Other,
+}
+
+#[derive_context_err]
+#[derive(Debug)]
+pub enum PartialError {
+    #[error(contextual, backtrace)]
+    Parse(std::num::ParseIntError),
+    #[error(contextual)]
+    Io(std::io::Error),
+    #[error(contextual, backtrace)]
+    Invalid,
+}
+
+fn parse(s: &str) -> Result<u32, Error> {
+    if s.is_empty() {
+        bail!(Error::Missing, "empty input");
+    }
+    s.parse::<u32>().context("parsing")
+}
+
+fn check(n: u32) -> Result<u32, PartialError> {
+    if n == 0 {
+        bail!(PartialError::Invalid, "zero");
+    }
+    Ok(n)
+}
+
+#[test]
+fn source() {
+    let err = parse("x").unwrap_err();
+    assert!(err.backtrace().is_some());
+    assert!(std::error::request_ref::<std::backtrace::Backtrace>(&err).is_some());
+}
+
+#[test]
+fn none() {
+    let err = None::<u32>.context("missing").unwrap_err();
+    assert!(matches!(err, Error::Missing(..)));
+    assert!(err.backtrace().is_some());
+}
+
+#[test]
+fn bail() {
+    let err = parse("").unwrap_err();
+    assert!(err.is_missing());
+    assert!(err.backtrace().is_some());
+    assert!(err.location().is_some());
+
+    let err = check(0).unwrap_err();
+    assert!(err.is_invalid());
+    assert!(err.backtrace().is_some());
+}
+
+#[test]
+fn per_variant() {
+    let err: PartialError = "x".parse::<u32>().context("parsing").unwrap_err();
+    assert!(err.backtrace().is_some());
+
+    let err: PartialError = std::fs::read("/nonexistent")
+        .context("reading")
+        .unwrap_err();
+    assert!(err.backtrace().is_none());
+
+    assert!(Error::Other.backtrace().is_none());
+}
+
+#[test]
+fn constructor() {
+    let err = PartialError::invalid("constructed");
+    assert!(err.backtrace().is_some());
+}
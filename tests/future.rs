@@ -0,0 +1,54 @@
+use std::future::ready;
+
+use context_err::{derive_context_err, Context, FutureContextErr};
+use futures::executor::block_on;
+
+#[derive_context_err]
+#[derive(Debug)]
+pub enum Error {
+    #[error(contextual)]
+    Io(std::io::Error),
+    #[error(contextual, none)]
+    Missing,
+}
+
+async fn read() -> Result<Vec<u8>, std::io::Error> {
+    std::fs::read("/nonexistent")
+}
+
+async fn run() -> Result<(), Error> {
+    let read = read().context("reading");
+    let lookup = ready(None::<u8>).with_context(|| format!("looking up {}", 1));
+    let _: Vec<u8> = read.await?;
+    let _: u8 = lookup.await?;
+    Ok(())
+}
+
+#[test]
+fn result() {
+    let err = block_on(run()).unwrap_err();
+    assert_eq!(err.to_string(), "reading");
+    assert!(err.is_io());
+}
+
+#[test]
+fn option() {
+    let err: Error = block_on(ready(None::<u8>).with_context(|| "looking up")).unwrap_err();
+    assert!(err.is_missing());
+    assert_eq!(err.to_string(), "looking up");
+}
+
+#[test]
+fn lazy_context_on_success() {
+    let ok: Result<u8, Error> =
+        block_on(ready(Some(1)).with_context(|| -> String { unreachable!() }));
+    assert_eq!(ok.unwrap(), 1);
+}
+
+#[test]
+fn results_use_context() {
+    let err: Error = std::fs::read("/nonexistent")
+        .context("reading")
+        .unwrap_err();
+    assert!(err.is_io());
+}
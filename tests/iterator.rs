@@ -0,0 +1,43 @@
+use context_err::{derive_context_err, IteratorContextErr};
+
+#[derive_context_err]
+#[derive(Debug)]
+pub enum Error {
+    #[error(contextual)]
+    Parse(std::num::ParseIntError),
+    #[error(contextual, none)]
+    Missing,
+}
+
+#[test]
+fn fixed_context() {
+    let results = ["1", "x", "3"]
+        .iter()
+        .map(|s| s.parse::<u32>())
+        .context("parsing")
+        .collect::<Vec<Result<u32, Error>>>();
+    assert_eq!(results[0].as_ref().unwrap(), &1);
+    assert_eq!(results[1].as_ref().unwrap_err().to_string(), "parsing");
+    assert_eq!(results[2].as_ref().unwrap(), &3);
+}
+
+#[test]
+fn lazy_context() {
+    let mut line = 0;
+    let parsed: Result<Vec<u32>, Error> = ["1", "2", "y"]
+        .iter()
+        .map(|s| s.parse::<u32>())
+        .with_context(|| {
+            line += 1;
+            format!("parsing error {line}")
+        })
+        .collect();
+    assert_eq!(parsed.unwrap_err().to_string(), "parsing error 1");
+}
+
+#[test]
+fn options() {
+    let found: Vec<Result<u8, Error>> = [Some(1), None].into_iter().context("looking up").collect();
+    assert_eq!(found[0].as_ref().unwrap(), &1);
+    assert!(found[1].as_ref().unwrap_err().is_missing());
+}
@@ -0,0 +1,92 @@
+use context_err::{derive_context_err, Context, ContextualError, Report};
+
+#[derive_context_err]
+#[derive(Debug)]
+#[error(contextual)]
+pub struct ConfigError(std::io::Error);
+
+#[derive_context_err]
+#[derive(Debug)]
+#[error(contextual)]
+pub struct SettingsError(ConfigError);
+
+#[derive_context_err(display = "chain")]
+#[derive(Debug)]
+pub enum ChainError {
+    #[error(contextual)]
+    Parse(std::num::ParseIntError),
+}
+
+#[derive_context_err(display = "chain")]
+#[derive(Debug)]
+#[error(contextual)]
+pub struct OuterChainError(ChainError);
+
+fn load() -> Result<(), SettingsError> {
+    let _: Vec<u8> = std::fs::read("/nonexistent")
+        .context("reading config")
+        .context("loading settings")?;
+    Ok(())
+}
+
+fn run() -> Result<(), Report<SettingsError>> {
+    load()?;
+    Ok(())
+}
+
+fn io_error() -> std::io::Error {
+    std::fs::read("/nonexistent").unwrap_err()
+}
+
+#[test]
+fn multi_line() {
+    let report = run().unwrap_err();
+    assert_eq!(
+        report.to_string(),
+        format!(
+            "loading settings\n\nCaused by:\n    0: reading config\n    1: {}",
+            io_error(),
+        ),
+    );
+    assert_eq!(format!("{report:?}"), report.to_string());
+}
+
+#[test]
+fn compact() {
+    let report = run().unwrap_err().compact();
+    assert_eq!(
+        report.to_string(),
+        format!("loading settings: reading config: {}", io_error()),
+    );
+    assert_eq!(report.into_error().context(), Some("loading settings"));
+}
+
+#[test]
+fn single_source() {
+    let single: ConfigError = Err::<(), _>(io_error())
+        .context("reading config")
+        .unwrap_err();
+    assert_eq!(
+        Report::new(single).to_string(),
+        format!("reading config\n\nCaused by:\n    {}", io_error()),
+    );
+}
+
+#[test]
+fn compact_chain() {
+    let parse = "p".parse::<u32>().unwrap_err();
+    let chain: ChainError = Err::<(), _>(parse.clone()).context("p").unwrap_err();
+    assert_eq!(
+        Report::new(chain).compact().to_string(),
+        format!("p: {parse}"),
+    );
+
+    let outer: OuterChainError = Err::<(), _>(parse.clone())
+        .context("inner")
+        .context("outer")
+        .unwrap_err();
+    assert_eq!(
+        Report::new(outer).compact().to_string(),
+        format!("outer: inner: {parse}"),
+    );
+}
@@ -0,0 +1,38 @@
+#![cfg(feature = "futures")]
+
+use context_err::{derive_context_err, StreamContextErr};
+use futures::{executor::block_on, stream, StreamExt, TryStreamExt};
+
+#[derive_context_err]
+#[derive(Debug)]
+pub enum Error {
+    #[error(contextual)]
+    Parse(std::num::ParseIntError),
+}
+
+#[test]
+fn fixed_context() {
+    let results: Vec<Result<u32, Error>> = block_on(
+        stream::iter(["1", "x"])
+            .map(|s| s.parse::<u32>())
+            .context("parsing")
+            .collect(),
+    );
+    assert_eq!(results[0].as_ref().unwrap(), &1);
+    assert_eq!(results[1].as_ref().unwrap_err().to_string(), "parsing");
+}
+
+#[test]
+fn lazy_context() {
+    let mut errors = 0;
+    let result: Result<Vec<u32>, Error> = block_on(
+        stream::iter(["x", "2"])
+            .map(|s| s.parse::<u32>())
+            .with_context(|| {
+                errors += 1;
+                format!("parsing error {errors}")
+            })
+            .try_collect(),
+    );
+    assert_eq!(result.unwrap_err().to_string(), "parsing error 1");
+}
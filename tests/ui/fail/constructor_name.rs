@@ -0,0 +1,22 @@
+use context_err::derive_context_err;
+
+#[derive_context_err(location, kind)]
+#[derive(Debug)]
+pub enum Error {
+    #[error(contextual)]
+    Io(std::io::Error),
+    #[error(contextual)]
+    Location,
+    #[error(contextual)]
+    Kind,
+    #[error(contextual)]
+    IsIo,
+    #[error(contextual)]
+    Crate,
+    #[error(contextual)]
+    HttpError,
+    #[error(contextual)]
+    HTTPError,
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+use context_err::{context, derive_context_err};
+
+#[derive_context_err(context_type = "&'static str")]
+#[derive(Debug)]
+pub enum Error {
+    #[error(contextual)]
+    Io(std::io::Error),
+}
+
+#[context("loading {path}")]
+fn load(path: &str) -> Result<String, Error> {
+    let text = std::fs::read_to_string(path)?;
+    Ok(text)
+}
+
+fn main() {}
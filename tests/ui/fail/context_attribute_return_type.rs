@@ -0,0 +1,8 @@
+use context_err::context;
+
+#[context("loading {path}")]
+fn load(path: &str) -> std::io::Result<Vec<u8>> {
+    std::fs::read(path)
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use context_err::derive_context_err;
+
+#[derive_context_err]
+#[derive(Debug)]
+pub enum Error {
+    #[error(contextual)]
+    HttpError(std::io::Error),
+    #[error(contextual)]
+    HTTPError(std::num::ParseIntError),
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use context_err::derive_context_err;
+
+#[derive_context_err]
+#[derive(Debug)]
+pub enum Error {
+    #[error(contextual, none)]
+    Missing,
+    #[error(contextual, none)]
+    Absent,
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use context_err::derive_context_err;
+
+#[derive_context_err]
+#[derive(Debug)]
+pub enum Error {
+    #[error(contextual)]
+    Read(std::io::Error),
+    #[error(contextual)]
+    Write(std::io::Error),
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use context_err::derive_context_err;
+
+#[derive_context_err]
+#[derive(Debug)]
+pub enum Error {
+    #[error(contextual)]
+    #[error("an io error occurred")]
+    Io(std::io::Error),
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+use context_err::derive_context_err;
+
+#[derive_context_err]
+#[derive(Debug)]
+pub enum Error<E: std::error::Error + 'static> {
+    #[error(contextual)]
+    Backend(E),
+    #[error(contextual)]
+    Io(std::io::Error),
+    #[error(contextual, none)]
+    Missing,
+}
+
+fn main() {}
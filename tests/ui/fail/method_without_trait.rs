@@ -0,0 +1,8 @@
+use context_err::derive_context_err;
+
+#[derive_context_err(method = "ctx")]
+#[derive(Debug)]
+#[error(contextual)]
+pub struct Error(std::io::Error);
+
+fn main() {}
@@ -0,0 +1,6 @@
+use context_err::derive_context_err;
+
+#[derive_context_err]
+fn not_an_error() {}
+
+fn main() {}
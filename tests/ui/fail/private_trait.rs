@@ -0,0 +1,12 @@
+mod errors {
+    use context_err::derive_context_err;
+
+    #[derive_context_err(trait = "ContextErr")]
+    #[derive(Debug)]
+    #[error(contextual)]
+    struct Error(std::io::Error);
+}
+
+use errors::ContextErr;
+
+fn main() {}
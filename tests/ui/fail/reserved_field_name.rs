@@ -0,0 +1,10 @@
+use context_err::derive_context_err;
+
+#[derive_context_err]
+#[derive(Debug)]
+#[error(contextual)]
+pub struct ReadError {
+    context: std::io::Error,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use context_err::derive_context_err;
+
+#[derive_context_err]
+#[derive(Debug)]
+pub enum Error {
+    #[error(contextual)]
+    Io(#[source] std::io::Error),
+}
+
+fn main() {}
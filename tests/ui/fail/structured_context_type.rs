@@ -0,0 +1,10 @@
+use context_err::derive_context_err;
+
+#[derive_context_err(structured, context_type = "String")]
+#[derive(Debug)]
+pub enum Error {
+    #[error(contextual)]
+    Io(std::io::Error),
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use context_err::derive_context_err;
+
+#[derive_context_err(trait_vis = "pub(crate)")]
+#[derive(Debug)]
+#[error(contextual)]
+pub struct Error(std::io::Error);
+
+fn main() {}
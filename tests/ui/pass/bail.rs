@@ -0,0 +1,52 @@
+use context_err::{bail, derive_context_err, ensure};
+
+#[derive_context_err(location)]
+#[derive(Debug)]
+pub enum Error {
+    #[error(contextual)]
+    Io(std::io::Error),
+    #[error(contextual, none)]
+    Missing,
+}
+
+#[derive_context_err]
+#[derive(Debug)]
+pub enum PlainError {
+    #[error(contextual, none)]
+    Missing,
+}
+
+#[derive(Debug)]
+pub struct Outer(PlainError);
+
+impl From<PlainError> for Outer {
+    fn from(err: PlainError) -> Self {
+        Outer(err)
+    }
+}
+
+fn find(id: u32) -> Result<u32, Error> {
+    ensure!(id > 0, Error::Missing, "invalid id {id}");
+    if id > 10 {
+        bail!(Error::Missing, "no user with id {}", id);
+    }
+    Ok(id)
+}
+
+fn outer() -> Result<(), Outer> {
+    bail!(PlainError::Missing, "converted");
+}
+
+fn main() {
+    assert_eq!(find(3).unwrap(), 3);
+
+    let err = find(0).unwrap_err();
+    assert_eq!(err.to_string(), "invalid id 0");
+    assert_eq!(err.location().unwrap().line(), 29);
+
+    let err = find(11).unwrap_err();
+    assert_eq!(err.as_missing(), Some("no user with id 11"));
+    assert_eq!(err.location().unwrap().line(), 31);
+
+    assert_eq!(outer().unwrap_err().0.to_string(), "converted");
+}
@@ -0,0 +1,59 @@
+use context_err::{context, derive_context_err};
+use futures::executor::block_on;
+
+#[derive_context_err(location)]
+#[derive(Debug)]
+pub enum Error {
+    #[error(contextual)]
+    Io(std::io::Error),
+    #[error(contextual)]
+    Parse(std::num::ParseIntError),
+    #[error(contextual, none)]
+    Missing,
+    #[error("too large: {0}")]
+    TooLarge(u32),
+}
+
+#[context("loading {path}")]
+fn load(path: &str) -> Result<u32, Error> {
+    let text = std::fs::read_to_string(path)?;
+    let n = text.trim().parse::<u32>()?;
+    if n > 10 {
+        // Errors which are already of the function's error type pass through untouched.
+        Err(Error::TooLarge(n))?;
+    }
+    Ok(n)
+}
+
+#[context("parsing {:?}", input)]
+fn parse(input: &str) -> Result<u32, Error> {
+    let first = input.split(',').next()?;
+    let n = first.parse::<u32>()?;
+    let nested = || -> Result<u32, std::num::ParseIntError> { Ok(input.len().to_string().parse()?) };
+    Ok(n + nested().unwrap())
+}
+
+struct Loader {
+    root: String,
+}
+
+impl Loader {
+    #[context("loading from {}", self.root)]
+    async fn load(&self) -> Result<Vec<u8>, Error> {
+        let data = std::fs::read(&self.root)?;
+        Ok(data)
+    }
+}
+
+fn main() {
+    let err = load("/nonexistent").unwrap_err();
+    assert!(err.is_io());
+    assert_eq!(err.to_string(), "loading /nonexistent");
+    assert_eq!(err.location().unwrap().line(), 19);
+
+    assert_eq!(parse("x").unwrap_err().to_string(), "parsing \"x\"");
+    assert_eq!(parse("1").unwrap(), 2);
+
+    let err = block_on(Loader { root: "/nonexistent".into() }.load()).unwrap_err();
+    assert_eq!(err.to_string(), "loading from /nonexistent");
+}
@@ -0,0 +1,16 @@
+use std::borrow::Cow;
+
+use context_err::{derive_context_err, Context};
+
+#[derive_context_err(context_type = "Cow<'static, str>")]
+#[derive(Debug)]
+pub enum Error {
+    #[error(contextual)]
+    Parse(std::num::ParseIntError),
+}
+
+fn main() {
+    let err: Error = "x".parse::<u32>().context("parsing").unwrap_err();
+    let Error::Parse(_, context) = &err;
+    assert!(matches!(context, Cow::Borrowed("parsing")));
+}
@@ -0,0 +1,48 @@
+use context_err::{derive_context_err, Context, ContextKv, ContextualError};
+
+#[derive_context_err]
+#[derive(Debug)]
+pub enum Error {
+    #[error(contextual)]
+    Io(std::io::Error),
+    #[error(contextual, none)]
+    Missing,
+    #[error("plain")]
+    Plain(#[source] std::fmt::Error),
+}
+
+#[derive_context_err(structured)]
+#[derive(Debug)]
+#[error(contextual)]
+pub struct StructuredError {
+    source: std::num::ParseIntError,
+}
+
+fn log<E: ContextualError>(err: &E) -> String {
+    match (err.context(), err.source_ref()) {
+        (Some(context), Some(source)) => format!("{context}: {source}"),
+        (Some(context), None) => context.to_owned(),
+        (None, _) => err.to_string(),
+    }
+}
+
+fn main() {
+    let err: Error = std::fs::read("/nonexistent").context("reading").unwrap_err();
+    assert!(log(&err).starts_with("reading: "));
+    assert!(err.source_ref().unwrap().is::<std::io::Error>());
+
+    let err: Error = None::<u8>.context("looking up").unwrap_err();
+    assert_eq!(log(&err), "looking up");
+
+    let err = Error::Plain(std::fmt::Error);
+    assert_eq!(ContextualError::context(&err), None);
+    assert!(err.source_ref().is_none());
+    assert_eq!(log(&err), "plain");
+
+    let err: StructuredError = "x"
+        .parse::<u32>()
+        .context_kv("parsing", [("input", "x")])
+        .unwrap_err();
+    assert_eq!(err.context(), Some("parsing"));
+    assert_eq!(log(&err), "parsing: invalid digit found in string");
+}
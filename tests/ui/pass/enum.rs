@@ -0,0 +1,23 @@
+use context_err::{derive_context_err, Context};
+
+#[derive_context_err]
+#[derive(Debug)]
+pub enum Error {
+    #[error(contextual)]
+    Parse(std::num::ParseIntError),
+    #[error(contextual)]
+    Io(std::io::Error),
+}
+
+fn parse(s: &str) -> Result<u32, Error> {
+    let n = s.parse::<u32>().context("parsing number")?;
+    std::fs::read_to_string(s).with_context(|| format!("reading {s}"))?;
+    Ok(n)
+}
+
+fn main() {
+    let err = parse("x").unwrap_err();
+    assert!(matches!(err, Error::Parse(..)));
+    assert_eq!(err.to_string(), "parsing number");
+    assert!(std::error::Error::source(&err).is_some());
+}
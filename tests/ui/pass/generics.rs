@@ -0,0 +1,120 @@
+use std::error::Error as StdError;
+use std::num::ParseIntError;
+
+use context_err::{derive_context_err, Context, ContextualError};
+
+#[derive_context_err(location)]
+#[derive(Debug)]
+pub enum Error<E>
+where
+    E: StdError + 'static,
+{
+    #[error(contextual)]
+    Backend(E),
+    #[error("invalid value {0}")]
+    Invalid(u32),
+    #[error(contextual, none)]
+    Missing,
+}
+
+/// Bounds which `thiserror` infers are not required up front.
+#[derive_context_err]
+#[derive(Debug)]
+pub enum UnboundedError<E: StdError> {
+    #[error(contextual)]
+    Backend(E),
+}
+
+#[derive_context_err]
+#[derive(Debug)]
+pub enum ValueError<T> {
+    #[error(contextual)]
+    Parse(ParseIntError),
+    #[error("bad value {0}")]
+    Bad(T),
+}
+
+#[derive_context_err]
+#[derive(Debug)]
+pub enum ParseError<'a> {
+    #[error(contextual)]
+    Int(ParseIntError),
+    #[error("unexpected input {0:?}")]
+    Unexpected(&'a str),
+}
+
+#[derive_context_err(structured)]
+#[derive(Debug)]
+#[error(contextual)]
+pub struct BufferError<const N: usize> {
+    source: std::io::Error,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("S")]
+pub struct S;
+
+mod dedicated {
+    use context_err::derive_context_err;
+
+    /// The generic parameter and source type share names with the generated impls' parameters.
+    #[derive_context_err(trait = "ContextT")]
+    #[derive(Debug)]
+    pub enum Error<T: std::fmt::Debug> {
+        #[error(contextual)]
+        S(super::S),
+        #[error("bad value {0:?}")]
+        Bad(T),
+    }
+
+    pub fn fail() -> Result<(), Error<u8>> {
+        Err(super::S).context("failing")
+    }
+}
+
+fn backend(value: Option<&str>) -> Result<u32, Error<std::io::Error>> {
+    let value = value.context("missing value")?;
+    std::fs::read(value).context("reading")?;
+    value.parse::<u32>().map_err(|_| Error::Invalid(0))
+}
+
+fn parse(input: &str) -> Result<u32, ParseError<'_>> {
+    if input.is_empty() {
+        return Err(ParseError::Unexpected(input));
+    }
+    input.parse().context("parsing")
+}
+
+fn buffer() -> Result<Vec<u8>, BufferError<16>> {
+    std::fs::read("/nonexistent").context("reading")
+}
+
+fn unbounded() -> Result<Vec<u8>, UnboundedError<std::io::Error>> {
+    std::fs::read("/nonexistent").context("reading")
+}
+
+fn value(input: &str) -> Result<u32, ValueError<i64>> {
+    match input.parse::<i64>().context("parsing")? {
+        n if n < 0 => Err(ValueError::Bad(n)),
+        n => Ok(n as u32),
+    }
+}
+
+fn main() {
+    let err = backend(Some("/nonexistent")).unwrap_err();
+    assert!(matches!(err, Error::Backend(..)));
+    assert!(err.location().is_some());
+    assert!(matches!(backend(None).unwrap_err(), Error::Missing(..)));
+
+    assert_eq!(parse("").unwrap_err().to_string(), "unexpected input \"\"");
+    assert_eq!(parse("x").unwrap_err().to_string(), "parsing");
+
+    assert!(buffer().unwrap_err().context_fields().is_empty());
+    assert_eq!(dedicated::fail().unwrap_err().to_string(), "failing");
+
+    let err = unbounded().unwrap_err();
+    assert_eq!(ContextualError::context(&err), Some("reading"));
+    assert!(err.source_ref().is_some());
+    assert_eq!(value("-1").unwrap_err().to_string(), "bad value -1");
+    assert_eq!(ContextualError::context(&value("x").unwrap_err()), Some("parsing"));
+}
@@ -0,0 +1,42 @@
+use std::collections::HashSet;
+
+use context_err::{derive_context_err, Context};
+
+#[derive_context_err(kind)]
+#[derive(Debug)]
+pub enum Error<E: std::error::Error + 'static> {
+    /// The backend failed.
+    #[error(contextual)]
+    Backend(E),
+    #[error(contextual, none)]
+    Missing,
+    #[error("invalid value {value}")]
+    Invalid { value: u32 },
+    #[cfg(any())]
+    #[error("never compiled")]
+    Disabled,
+}
+
+#[derive_context_err(kind)]
+#[derive(Debug)]
+enum Empty {}
+
+fn status(err: &Error<std::io::Error>) -> u16 {
+    match err.kind() {
+        ErrorKind::Backend => 502,
+        ErrorKind::Missing => 404,
+        ErrorKind::Invalid => 400,
+    }
+}
+
+fn main() {
+    let err: Error<std::io::Error> = std::fs::read("/nonexistent").context("reading").unwrap_err();
+    assert_eq!(status(&err), 502);
+    assert_eq!(status(&Error::Invalid { value: 3 }), 400);
+
+    let kinds: HashSet<ErrorKind> = [ErrorKind::Missing, ErrorKind::Missing].into();
+    assert_eq!(kinds.len(), 1);
+
+    let _ = |empty: Empty| empty.kind();
+    let _: Option<EmptyKind> = None;
+}
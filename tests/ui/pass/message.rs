@@ -0,0 +1,70 @@
+use context_err::{bail, derive_context_err, Context, ContextualError};
+
+#[derive_context_err(location)]
+#[derive(Debug)]
+pub enum Error {
+    #[error(contextual)]
+    Io(std::io::Error),
+    #[error(contextual)]
+    Invalid,
+    #[error(contextual, none)]
+    Missing,
+}
+
+#[derive_context_err]
+#[derive(Debug)]
+#[error(contextual)]
+pub struct InvariantError;
+
+// Constructors whose names are keywords are raw identifiers.
+#[derive_context_err]
+#[derive(Debug)]
+pub enum KeywordError {
+    #[error(contextual)]
+    Type,
+    #[error(contextual)]
+    Move,
+}
+
+fn check(count: usize) -> Result<usize, Error> {
+    if count > 3 {
+        return Err(Error::invalid(format!("{count} items is too many")));
+    }
+    if count == 0 {
+        bail!(Error::Invalid, "no items");
+    }
+    Ok(count)
+}
+
+fn first(items: &[u32]) -> Result<u32, Error> {
+    items.first().copied().context("no first item")
+}
+
+fn main() {
+    assert_eq!(check(2).unwrap(), 2);
+
+    let err = check(4).unwrap_err();
+    assert_eq!(err.to_string(), "4 items is too many");
+    assert!(err.is_invalid());
+    assert_eq!(err.as_invalid(), Some("4 items is too many"));
+    assert_eq!(err.location().unwrap().line(), 31);
+    assert!(std::error::Error::source(&err).is_none());
+    assert!(err.source_ref().is_none());
+
+    let err = check(0).unwrap_err();
+    assert_eq!(err.into_invalid().unwrap(), "no items");
+
+    let err = first(&[]).unwrap_err();
+    assert!(err.is_missing());
+    assert_eq!(err.context(), Some("no first item"));
+
+    let err = Error::missing("constructed directly");
+    assert_eq!(err.as_missing(), Some("constructed directly"));
+
+    let err = InvariantError::new("broken invariant");
+    assert_eq!(err.to_string(), "broken invariant");
+    assert_eq!(err.context(), Some("broken invariant"));
+
+    assert!(KeywordError::r#type("bad type").is_type());
+    assert!(KeywordError::r#move("bad move").is_move());
+}
@@ -0,0 +1,25 @@
+use context_err::{derive_context_err, Context};
+
+#[derive_context_err]
+#[derive(Debug)]
+#[error(contextual)]
+pub struct SharedError(std::io::Error);
+
+#[derive_context_err(trait = "CtxErr", method = "ctx")]
+#[derive(Debug)]
+#[error(contextual)]
+pub struct CtxError(std::io::Error);
+
+#[derive_context_err(trait = "WrapErr", method = "wrap_err", with_method = "wrap_err_with")]
+#[derive(Debug)]
+#[error(contextual)]
+pub struct WrapError(std::io::Error);
+
+fn main() {
+    let _: Result<Vec<u8>, SharedError> = std::fs::read("/nonexistent").context("reading");
+    let _: Result<Vec<u8>, CtxError> = std::fs::read("/nonexistent").ctx("reading");
+    let _: Result<Vec<u8>, CtxError> = std::fs::read("/nonexistent").with_ctx(|| "reading");
+    let _: Result<Vec<u8>, WrapError> = std::fs::read("/nonexistent").wrap_err("reading");
+    let _: Result<Vec<u8>, WrapError> =
+        std::fs::read("/nonexistent").wrap_err_with(|| "reading");
+}
@@ -0,0 +1,26 @@
+use context_err::{derive_context_err, Context};
+
+#[derive_context_err]
+#[derive(Debug)]
+pub enum Error {
+    #[error(contextual)]
+    Parse(std::num::ParseIntError),
+    #[error("value {0} is out of range")]
+    OutOfRange(u32),
+    #[error("an io error occurred")]
+    Io(#[from] std::io::Error),
+}
+
+fn parse(s: &str) -> Result<u32, Error> {
+    let n = s.parse::<u32>().context("parsing number")?;
+    if n > 10 {
+        return Err(Error::OutOfRange(n));
+    }
+    std::fs::read_to_string(s)?;
+    Ok(n)
+}
+
+fn main() {
+    assert_eq!(parse("11").unwrap_err().to_string(), "value 11 is out of range");
+    assert_eq!(parse("x").unwrap_err().to_string(), "parsing number");
+}
@@ -0,0 +1,40 @@
+use context_err::{derive_context_err, Context};
+
+#[derive_context_err]
+#[derive(Debug)]
+#[error(contextual)]
+pub struct Error1(std::io::Error);
+
+#[derive_context_err]
+#[derive(Debug)]
+#[error(contextual)]
+pub struct Error2(std::io::Error);
+
+fn read1(path: &str) -> Result<String, Error1> {
+    std::fs::read_to_string(path).context("reading config")
+}
+
+fn read2(path: &str) -> Result<String, Error2> {
+    let config = Context::<Error2>::context(std::fs::read_to_string(path), "reading config")?;
+    Ok(config)
+}
+
+mod dedicated {
+    use context_err::derive_context_err;
+
+    #[derive_context_err(trait = "ContextErr3")]
+    #[derive(Debug)]
+    #[error(contextual)]
+    pub struct Error3(std::io::Error);
+
+    pub fn read3(path: &str) -> Result<String, Error3> {
+        let config = std::fs::read_to_string(path).context("reading config")?;
+        Ok(config)
+    }
+}
+
+fn main() {
+    assert!(read1("/nonexistent").is_err());
+    assert!(read2("/nonexistent").is_err());
+    assert!(dedicated::read3("/nonexistent").is_err());
+}
@@ -0,0 +1,16 @@
+use context_err::{derive_context_err, Context};
+
+#[derive_context_err]
+#[derive(Debug)]
+#[error(contextual)]
+pub struct ReadError {
+    source: std::io::Error,
+}
+
+fn main() {
+    let err: ReadError = std::fs::read("/nonexistent")
+        .context("reading file")
+        .unwrap_err();
+    assert_eq!(err.context, "reading file");
+    assert_eq!(err.source.kind(), std::io::ErrorKind::NotFound);
+}
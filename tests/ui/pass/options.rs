@@ -0,0 +1,24 @@
+use context_err::{derive_context_err, Context};
+
+#[derive_context_err(display = "chain", location)]
+#[derive(Debug)]
+pub enum Error {
+    #[error(contextual)]
+    Parse(std::num::ParseIntError),
+    #[error(contextual, fmt = "{context} (caused by {source})")]
+    Float(std::num::ParseFloatError),
+    #[error(contextual, none)]
+    Missing,
+}
+
+fn main() {
+    let err: Error = "x".parse::<u32>().context("parsing").unwrap_err();
+    assert_eq!(err.to_string(), "parsing: invalid digit found in string");
+    assert_eq!(err.location().unwrap().file(), file!());
+
+    let err: Error = "x".parse::<f64>().context("parsing").unwrap_err();
+    assert_eq!(err.to_string(), "parsing (caused by invalid float literal)");
+
+    let err: Error = None::<u32>.context("looking up user").unwrap_err();
+    assert_eq!(err.to_string(), "looking up user");
+}
@@ -0,0 +1,20 @@
+use context_err::{derive_context_err, Context, ContextKv, Value};
+
+#[derive_context_err(structured)]
+#[derive(Debug)]
+pub enum Error {
+    #[error(contextual)]
+    Parse(std::num::ParseIntError),
+}
+
+fn main() {
+    let err: Error = "x"
+        .parse::<u32>()
+        .context_kv("parsing", [("input", "x")])
+        .unwrap_err();
+    assert_eq!(err.to_string(), "parsing (input=x)");
+    assert_eq!(err.context_fields(), &[("input", Value::from("x"))]);
+
+    let err: Error = "x".parse::<u32>().context("parsing").unwrap_err();
+    assert!(err.context_fields().is_empty());
+}
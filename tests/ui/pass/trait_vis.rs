@@ -0,0 +1,22 @@
+#![deny(private_interfaces, private_bounds)]
+
+mod errors {
+    use context_err::derive_context_err;
+
+    #[derive_context_err(trait = "ContextCrateErr")]
+    #[derive(Debug)]
+    #[error(contextual)]
+    pub(crate) struct CrateError(std::io::Error);
+
+    #[derive_context_err(trait = "ContextPubErr", trait_vis = "pub(crate)")]
+    #[derive(Debug)]
+    #[error(contextual)]
+    pub struct PubError(std::num::ParseIntError);
+}
+
+use errors::{ContextCrateErr, ContextPubErr};
+
+fn main() {
+    let _: Result<Vec<u8>, errors::CrateError> = std::fs::read("/nonexistent").context("reading");
+    let _: Result<u32, errors::PubError> = "x".parse::<u32>().context("parsing");
+}
@@ -0,0 +1,59 @@
+use std::borrow::Cow;
+
+use context_err::{derive_context_err, Context, StructuredContext};
+
+#[derive_context_err(location)]
+#[derive(Debug)]
+pub enum Error {
+    #[error(contextual)]
+    Io(std::io::Error),
+    #[error(contextual)]
+    ParseInt { source: std::num::ParseIntError },
+    #[error(contextual, none)]
+    Missing,
+    #[error("plain")]
+    Plain,
+}
+
+#[derive_context_err(context_type = "Cow<'static, str>")]
+#[derive(Debug)]
+pub enum CowError {
+    #[error(contextual)]
+    HTTPError(std::io::Error),
+}
+
+#[derive_context_err(structured)]
+#[derive(Debug)]
+pub enum StructuredError {
+    #[error(contextual)]
+    Io(std::io::Error),
+}
+
+fn main() {
+    let err: Error = std::fs::read("/nonexistent").context("reading").unwrap_err();
+    assert!(err.is_io());
+    assert!(!err.is_parse_int());
+    let (source, context) = err.as_io().unwrap();
+    assert_eq!(source.kind(), std::io::ErrorKind::NotFound);
+    assert_eq!(context, "reading");
+    assert!(err.as_missing().is_none());
+    assert_eq!(err.into_io().unwrap().kind(), std::io::ErrorKind::NotFound);
+
+    let err: Error = "x".parse::<u32>().context("parsing").unwrap_err();
+    assert_eq!(err.as_parse_int().unwrap().1, "parsing");
+    let err = err.into_io().unwrap_err();
+    assert!(err.into_parse_int().is_ok());
+
+    let err: Error = None::<u32>.context("looking up").unwrap_err();
+    assert_eq!(err.as_missing(), Some("looking up"));
+    assert_eq!(err.into_missing().unwrap(), "looking up");
+    assert!(!Error::Plain.is_io());
+
+    let err: CowError = std::fs::read("/nonexistent").context("reading").unwrap_err();
+    assert!(err.is_http_error());
+    assert_eq!(err.as_http_error().unwrap().1, "reading");
+
+    let err: StructuredError = std::fs::read("/nonexistent").context("reading").unwrap_err();
+    let (_, context): (_, &StructuredContext) = err.as_io().unwrap();
+    assert_eq!(context.message(), "reading");
+}